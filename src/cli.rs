@@ -0,0 +1,247 @@
+use std::error::Error;
+
+use crate::{
+    engine::{config, export, fs, gfx, lint, markup, save, story::Story, validate},
+    game,
+    screens::graph::GameGraph,
+};
+
+const USAGE: &str = "usage: call-of-cthulhu [command] [options]
+
+commands:
+  play      play the game (default)
+  validate  check the data files and the story graph
+  graph     print the story graph
+  stats     print story statistics
+
+options:
+  --data PATH                     data directory instead of the one in config.json
+  --screen N                      start new games on screen N (play)
+  --format dot|mermaid|json       output format of the graph (default dot)
+  --visits SLOT                   colour the graph by the visits in a save slot
+  -h, --help                      print this help";
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Play,
+    Validate,
+    Graph,
+    Stats,
+}
+
+#[derive(Debug, PartialEq)]
+enum Format {
+    Dot,
+    Mermaid,
+    Json,
+}
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    command: Command,
+    data: Option<String>,
+    screen: Option<usize>,
+    format: Format,
+    visits: Option<String>,
+}
+
+fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, Box<dyn Error>> {
+    let mut parsed = Args {
+        command: Command::Play,
+        data: None,
+        screen: None,
+        format: Format::Dot,
+        visits: None,
+    };
+    let mut command = None;
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--data" => parsed.data = Some(value()?),
+            "--screen" => {
+                let screen = value()?;
+                parsed.screen = Some(
+                    screen
+                        .parse()
+                        .map_err(|_| format!("invalid screen number: {}", screen))?,
+                );
+            }
+            "--visits" => parsed.visits = Some(value()?),
+            "--format" => {
+                parsed.format = match value()?.as_str() {
+                    "dot" => Format::Dot,
+                    "mermaid" => Format::Mermaid,
+                    "json" => Format::Json,
+                    other => return Err(format!("unknown graph format: {}", other).into()),
+                }
+            }
+            _ if command.is_none() && !arg.starts_with('-') => {
+                command = Some(match arg.as_str() {
+                    "play" => Command::Play,
+                    "validate" => Command::Validate,
+                    "graph" => Command::Graph,
+                    "stats" => Command::Stats,
+                    other => return Err(format!("unknown command: {}", other).into()),
+                });
+            }
+            _ => return Err(format!("unexpected argument: {}", arg).into()),
+        }
+    }
+    if let Some(command) = command {
+        parsed.command = command;
+    }
+    Ok(Some(parsed))
+}
+
+// entry point of the binary, `args` without the program name
+pub fn run(args: impl Iterator<Item = String>) -> Result<(), Box<dyn Error>> {
+    let args = match parse(args) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+        Err(e) => {
+            eprintln!("{}\n", USAGE);
+            return Err(e);
+        }
+    };
+    let mut cfg = config::Config::load()?;
+    if let Some(data) = args.data {
+        cfg.data_path = data;
+    }
+    match args.command {
+        Command::Play => game::play_with(cfg, args.screen),
+        Command::Validate => validate(&cfg),
+        Command::Graph => {
+            let story = Story::load(&cfg)?;
+            let graph = GameGraph::load(&story, &cfg);
+            let visits = match &args.visits {
+                Some(slot) => Some(save::read_slot(&cfg, slot)?.visited),
+                None => None,
+            };
+            let out = match args.format {
+                Format::Dot => export::to_dot(&graph, visits.as_ref()),
+                Format::Mermaid => export::to_mermaid(&graph, visits.as_ref()),
+                Format::Json => export::to_json(&graph, &story, visits.as_ref())?,
+            };
+            print!("{}", out);
+            Ok(())
+        }
+        Command::Stats => stats(&cfg),
+    }
+}
+
+fn validate(cfg: &config::Config) -> Result<(), Box<dyn Error>> {
+    let story = Story::load(cfg)?;
+    validate::validate_screens(&story, cfg)?;
+    let graph = GameGraph::load(&story, cfg);
+    lint::check(&graph, &story)?;
+    println!("{}: {} screens ok", story.title, story.screens.len());
+    Ok(())
+}
+
+fn stats(cfg: &config::Config) -> Result<(), Box<dyn Error>> {
+    let story = Story::load(cfg)?;
+    let graph = GameGraph::load(&story, cfg);
+
+    let mut words = Vec::new();
+    for &screen_no in &story.screens {
+        // words split over two lines are counted once, markup is not a word
+        let text = gfx::reflow(&fs::read_text(screen_no, cfg).unwrap_or_default());
+        let text = markup::parse(&text).text;
+        words.push((text.split_whitespace().count(), screen_no));
+    }
+    let total_words = words.iter().map(|(count, _)| count).sum::<usize>();
+    let choices = story
+        .screens
+        .iter()
+        .filter(|s| !graph.endings.contains(s))
+        .map(|s| graph.choices.get(s).map(Vec::len).unwrap_or(0))
+        .collect::<Vec<_>>();
+    let total_choices = choices.iter().sum::<usize>();
+
+    println!("{}", story.title);
+    for (key, value) in &story.metadata {
+        println!("  {}: {}", key, value);
+    }
+    println!("screens:            {}", story.screens.len());
+    println!("start screen:       {}", story.start);
+    println!("endings:            {}", graph.endings.len());
+    println!("locations:          {}", graph.locations.len());
+    println!("choices:            {}", total_choices);
+    println!(
+        "average branching:  {:.2}",
+        total_choices as f64 / choices.len().max(1) as f64
+    );
+    println!("words:              {}", total_words);
+    println!(
+        "words per screen:   {:.0}",
+        total_words as f64 / words.len().max(1) as f64
+    );
+    if let Some((count, screen_no)) = words.iter().max() {
+        println!("longest screen:     {} ({} words)", screen_no, count);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Result<Option<Args>, Box<dyn Error>> {
+        parse(line.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn no_arguments_play_the_game() {
+        let parsed = args("").unwrap().unwrap();
+        assert_eq!(parsed.command, Command::Play);
+        assert_eq!(parsed.data, None);
+        assert_eq!(parsed.screen, None);
+        assert_eq!(parsed.format, Format::Dot);
+    }
+
+    #[test]
+    fn commands_and_options_in_any_order() {
+        let parsed = args("--data stories/cthulhu play --screen 17")
+            .unwrap()
+            .unwrap();
+        assert_eq!(parsed.command, Command::Play);
+        assert_eq!(parsed.data.as_deref(), Some("stories/cthulhu"));
+        assert_eq!(parsed.screen, Some(17));
+        let parsed = args("graph --format mermaid --visits quicksave")
+            .unwrap()
+            .unwrap();
+        assert_eq!(parsed.command, Command::Graph);
+        assert_eq!(parsed.format, Format::Mermaid);
+        assert_eq!(parsed.visits.as_deref(), Some("quicksave"));
+        assert_eq!(
+            args("--format json graph").unwrap().unwrap().format,
+            Format::Json
+        );
+        assert_eq!(
+            args("validate").unwrap().unwrap().command,
+            Command::Validate
+        );
+        assert_eq!(args("stats").unwrap().unwrap().command, Command::Stats);
+    }
+
+    #[test]
+    fn help_is_not_an_error() {
+        assert!(args("--help").unwrap().is_none());
+        assert!(args("graph -h").unwrap().is_none());
+    }
+
+    #[test]
+    fn bad_arguments_are_reported() {
+        let error = |line| args(line).err().unwrap().to_string();
+        assert_eq!(error("--screen"), "--screen needs a value");
+        assert_eq!(error("--screen x"), "invalid screen number: x");
+        assert_eq!(error("--format svg"), "unknown graph format: svg");
+        assert_eq!(error("replay"), "unknown command: replay");
+        assert_eq!(error("play validate"), "unexpected argument: validate");
+        assert_eq!(error("--verbose"), "unexpected argument: --verbose");
+    }
+}
@@ -0,0 +1,40 @@
+use crate::{effects::math, engine::fs::Bitmap};
+
+// Distorts the image the way the investigator's mind does.
+// `amount` goes from 0.0 (sane, image untouched) to 1.0 (raving mad),
+// `t` is the time in seconds and drives the wobble of the rows.
+pub(crate) fn apply(image: &mut Bitmap, amount: f32, t: f32) {
+    let amount = amount.clamp(0.0, 1.0);
+    if amount <= 0.0 {
+        return;
+    }
+
+    // drain the colours towards a sickly grey-green
+    for row in image.iter_mut() {
+        for pixel in row.iter_mut() {
+            *pixel = desaturate(*pixel, amount);
+        }
+    }
+
+    // rows start to sway once the mind is slipping
+    let max_shift = (amount - 0.25).max(0.0) * 8.0;
+    if max_shift < 1.0 {
+        return;
+    }
+    for (y, row) in image.iter_mut().enumerate() {
+        let shift = (max_shift * math::sin_lut_f32(y as f32 * 0.35 + t * 2.0)).round() as isize;
+        let len = row.len() as isize;
+        if shift == 0 || len == 0 {
+            continue;
+        }
+        row.rotate_right(shift.rem_euclid(len) as usize);
+    }
+}
+
+fn desaturate((r, g, b): (u8, u8, u8), amount: f32) -> (u8, u8, u8) {
+    let (r, g, b) = (r as f32, g as f32, b as f32);
+    let l = 0.299 * r + 0.587 * g + 0.114 * b;
+    let tint = (l * 0.9, l * 1.05, l * 0.85);
+    let blend = |c: f32, target: f32| (c + amount * (target - c)).round().clamp(0.0, 255.0) as u8;
+    (blend(r, tint.0), blend(g, tint.1), blend(b, tint.2))
+}
@@ -1,2 +1,4 @@
+pub(crate) mod madness;
 mod math;
+#[allow(dead_code)] // the rain effect is not shown on any screen yet
 mod rain;
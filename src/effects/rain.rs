@@ -1,4 +1,7 @@
-use std::time::{Duration, Instant};
+use std::{
+    f32::consts::{PI, TAU},
+    time::{Duration, Instant},
+};
 
 use rand::RngExt;
 
@@ -81,14 +84,12 @@ impl Rain {
         colors
     }
 
-    pub(crate) fn generate_wave(&self, fb: &mut Vec<Vec<f32>>) {
-        // let mut y = -3.14;
-
+    pub(crate) fn generate_wave(&self, fb: &mut [Vec<f32>]) {
         let h = self.height as f32;
         let w = self.width as f32;
 
-        let dx = 6.28 / w;
-        let dy = 6.28 / h;
+        let dx = TAU / w;
+        let dy = TAU / h;
         for rain_drop in self.rain_drops.iter() {
             let tx = rain_drop.x;
             let ty = rain_drop.y;
@@ -98,14 +99,13 @@ impl Rain {
 
             let t = rain_drop.t.elapsed().as_secs_f32();
 
-            let mut y = -3.14 + y_top * 6.28;
-            for yy in 0..self.height {
-                let mut x = -3.14 + x_left * 6.28;
+            let mut y = -PI + y_top * TAU;
+            for row in fb.iter_mut().take(self.height) {
+                let mut x = -PI + x_left * TAU;
 
-                for xx in 0..self.width {
-                    // fb[yy][xx] += wave_xy(rain_drop.k, x, y, t);
-                    fb[yy][xx] += wave_xy_lut(rain_drop.k, x, y, t);
-                    // fb[yy][xx] = 0.0;
+                for cell in row.iter_mut().take(self.width) {
+                    // *cell += wave_xy(rain_drop.k, x, y, t);
+                    *cell += wave_xy_lut(rain_drop.k, x, y, t);
                     x += dx;
                 }
                 y += dy;
@@ -130,7 +130,7 @@ fn water_rgb(a_norm: f32, desaturation: f32, tint: f32) -> (u8, u8, u8) {
     } else {
         let a_norm = -a_norm;
         (
-            (0.0 + (0.0 - 0.0) * a_norm),
+            0.0,
             (120.0 - (120.0 - 30.0) * a_norm),
             (200.0 - (200.0 - 80.0) * a_norm),
         )
@@ -232,7 +232,7 @@ pub fn gen_wave_xy_lookup(k: f32) {
     }
 }
 
-fn wave_xy_lut(k: f32, x: f32, y: f32, t: f32) -> f32 {
+fn wave_xy_lut(_k: f32, x: f32, y: f32, t: f32) -> f32 {
     // Use the lookup table
 
     let lookup_table = LOOKUP_TABLE.read().unwrap();
@@ -0,0 +1,219 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::{Read, Write},
+    path::PathBuf,
+};
+
+use serde::Deserialize;
+
+use crate::{
+    engine::{config, save},
+    screens::graph::GameGraph,
+};
+
+// what has to happen for an achievement to unlock
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Condition {
+    VisitedScreen(usize),   // the given screen was visited
+    ReachedEnding(usize),   // the story ended on the given screen
+    DistinctScreens(usize), // at least this many different screens were visited
+    AllLocations,           // every location of the story was seen
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct Achievement {
+    pub(crate) id: String,
+    pub(crate) title: String,
+    pub(crate) description: String,
+    #[serde(default)]
+    pub(crate) hidden: bool, // title and description stay secret until unlocked
+    pub(crate) condition: Condition,
+}
+
+impl Achievement {
+    fn is_met(&self, graph: &GameGraph) -> bool {
+        match &self.condition {
+            Condition::VisitedScreen(screen_no) => graph.visited.contains_key(screen_no),
+            Condition::ReachedEnding(screen_no) => {
+                graph.endings.contains(screen_no) && graph.visited.contains_key(screen_no)
+            }
+            Condition::DistinctScreens(count) => graph.visited.len() >= *count,
+            Condition::AllLocations => {
+                let all = graph.locations.values().collect::<HashSet<_>>();
+                let seen = graph
+                    .locations
+                    .iter()
+                    .filter(|(screen_no, _)| graph.visited.contains_key(*screen_no))
+                    .map(|(_, location)| location)
+                    .collect::<HashSet<_>>();
+                !all.is_empty() && seen.len() == all.len()
+            }
+        }
+    }
+}
+
+pub struct Achievements {
+    pub(crate) list: Vec<Achievement>,
+    pub(crate) unlocked: HashMap<String, u64>, // achievement id -> unlock time (unix seconds)
+}
+
+impl Achievements {
+    // read the definitions from the data path and the unlocks from the save path,
+    // a story without achievements.json has none
+    pub fn load(config: &config::Config) -> Result<Self, Box<dyn std::error::Error>> {
+        let file_path = format!("{}/achievements.json", config.data_path);
+        let list = match File::open(file_path) {
+            Ok(mut file) => {
+                let mut contents = String::new();
+                file.read_to_string(&mut contents)?;
+                serde_json::from_str(&contents)?
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let unlocked = match File::open(unlocked_file(config)) {
+            Ok(mut file) => {
+                let mut contents = String::new();
+                file.read_to_string(&mut contents)?;
+                serde_json::from_str(&contents)?
+            }
+            Err(_) => HashMap::new(), // nothing unlocked yet
+        };
+        Ok(Self { list, unlocked })
+    }
+
+    // unlock everything the current progress qualifies for,
+    // returns the newly unlocked achievements
+    pub fn evaluate(&mut self, graph: &GameGraph) -> Vec<Achievement> {
+        let now = save::now();
+        let mut newly_unlocked = Vec::new();
+        for achievement in self.list.iter() {
+            if !self.unlocked.contains_key(&achievement.id) && achievement.is_met(graph) {
+                self.unlocked.insert(achievement.id.clone(), now);
+                newly_unlocked.push(achievement.clone());
+            }
+        }
+        newly_unlocked
+    }
+
+    pub fn unlocked_at(&self, id: &str) -> Option<u64> {
+        self.unlocked.get(id).copied()
+    }
+
+    pub fn persist(&self, config: &config::Config) -> Result<(), Box<dyn std::error::Error>> {
+        let file_path = unlocked_file(config);
+        if let Some(dir) = file_path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let contents = serde_json::to_string_pretty(&self.unlocked)?;
+        let mut file = File::create(file_path)?;
+        file.write_all(contents.as_bytes())?;
+        Ok(())
+    }
+}
+
+// kept in a subdirectory so that it never shows up as a save slot
+fn unlocked_file(config: &config::Config) -> PathBuf {
+    save::save_dir(config)
+        .join("achievements")
+        .join("unlocked.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn achievements(list: &str) -> Achievements {
+        Achievements {
+            list: serde_json::from_str(list).unwrap(),
+            unlocked: HashMap::new(),
+        }
+    }
+
+    fn graph(path: &[usize]) -> GameGraph {
+        let mut graph = GameGraph::new();
+        graph.endings.insert(77);
+        graph.locations.insert(1, "Arkham".to_string());
+        graph.locations.insert(5, "Boston".to_string());
+        for &screen_no in path {
+            graph.visit(screen_no, None);
+        }
+        graph
+    }
+
+    #[test]
+    fn achievements_unlock_once_their_condition_is_met() {
+        let mut achievements = achievements(
+            r#"[
+                {"id": "first", "title": "", "description": "", "condition": {"visited_screen": 1}},
+                {"id": "three", "title": "", "description": "", "condition": {"distinct_screens": 3}},
+                {"id": "asylum", "title": "", "description": "", "hidden": true,
+                 "condition": {"reached_ending": 77}},
+                {"id": "travel", "title": "", "description": "", "condition": "all_locations"}
+            ]"#,
+        );
+        let ids =
+            |unlocked: Vec<Achievement>| unlocked.into_iter().map(|a| a.id).collect::<Vec<_>>();
+        assert_eq!(
+            ids(achievements.evaluate(&graph(&[0, 1, 1]))),
+            vec!["first"]
+        );
+        // already unlocked achievements are not reported again
+        assert!(achievements.evaluate(&graph(&[0, 1])).is_empty());
+        assert_eq!(
+            ids(achievements.evaluate(&graph(&[0, 1, 5, 77]))),
+            vec!["three", "asylum", "travel"]
+        );
+        assert!(achievements.list[2].hidden);
+        assert!(achievements.unlocked_at("first").is_some());
+    }
+
+    #[test]
+    fn an_ending_counts_only_when_it_is_an_ending() {
+        let mut achievements = achievements(
+            r#"[{"id": "end", "title": "", "description": "", "condition": {"reached_ending": 5}}]"#,
+        );
+        assert!(achievements.evaluate(&graph(&[0, 5])).is_empty());
+    }
+
+    #[test]
+    fn unlocks_are_persisted_across_runs() {
+        let cfg = config::Config {
+            data_path: "assets/".to_string(),
+            save_path: Some(
+                std::env::temp_dir()
+                    .join(format!("coc-achievements-{}", std::process::id()))
+                    .to_string_lossy()
+                    .into_owned(),
+            ),
+            ..config::Config::default()
+        };
+        let mut achievements = Achievements::load(&cfg).unwrap();
+        assert!(achievements.unlocked.is_empty());
+        achievements.evaluate(&graph(&[0, 1]));
+        achievements.persist(&cfg).unwrap();
+        let loaded = Achievements::load(&cfg).unwrap();
+        assert_eq!(loaded.unlocked, achievements.unlocked);
+        assert!(loaded.unlocked_at("first_steps").is_some());
+        std::fs::remove_dir_all(save::save_dir(&cfg)).unwrap();
+    }
+
+    #[test]
+    fn a_story_without_achievements_has_none() {
+        let empty = std::env::temp_dir()
+            .join(format!("coc-no-achievements-{}", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let cfg = config::Config {
+            data_path: empty.clone(),
+            save_path: Some(empty),
+            ..config::Config::default()
+        };
+        let mut achievements = Achievements::load(&cfg).unwrap();
+        assert!(achievements.list.is_empty());
+        assert!(achievements.evaluate(&graph(&[0, 1])).is_empty());
+    }
+}
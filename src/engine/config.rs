@@ -2,6 +2,12 @@ use std::{io::Read, str::FromStr};
 
 use serde::Deserialize;
 
+use crate::engine::{
+    gfx::TextPosition,
+    graphics::Graphics,
+    palette::{ColorDepth, Dither},
+};
+
 #[derive(Deserialize)]
 pub struct Config {
     pub(crate) scale_quality: bool,
@@ -9,6 +15,16 @@ pub struct Config {
     pub(crate) debug: bool,
     pub(crate) data_path: String,
     pub(crate) notifications: Option<bool>,
+    pub(crate) save_path: Option<String>,
+    pub(crate) dice_seed: Option<u64>, // fixed seed makes all rolls reproducible
+    pub(crate) hardcore: Option<bool>, // no taking back choices with Backspace
+    pub(crate) instant_text: Option<bool>, // no typewriter, the whole text at once
+    pub(crate) layout: Option<TextPosition>, // "left", "right" or "bottom"
+    pub(crate) text_ratio: Option<f32>, // share of the screen taken by the text window
+    pub(crate) graphics: Option<Graphics>, // "block", "sixel", "kitty", "ascii" or "braille", detected if not set
+    pub(crate) colors: Option<ColorDepth>, // "truecolor", "256" or "16", detected if not set
+    pub(crate) dither: Option<Dither>,     // "none", "ordered" or "floyd-steinberg"
+    pub(crate) ascii_edges: Option<bool>,  // ascii images outline the edges with | / - \
 }
 
 impl Default for Config {
@@ -19,6 +35,16 @@ impl Default for Config {
             debug: false,
             data_path: String::from("data/"),
             notifications: None,
+            save_path: None,
+            dice_seed: None,
+            hardcore: None,
+            instant_text: None,
+            layout: None,
+            text_ratio: None,
+            graphics: None,
+            colors: None,
+            dither: None,
+            ascii_edges: None,
         }
     }
 }
@@ -26,7 +52,12 @@ impl Default for Config {
 impl Config {
     pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
         let file_path = "config.json";
-        let mut file = std::fs::File::open(file_path).expect("Failed to open config file");
+        let mut file = match std::fs::File::open(file_path) {
+            Ok(file) => file,
+            // e.g. the authoring commands run elsewhere, with --data pointing at the story
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(format!("Failed to open {}: {}", file_path, e).into()),
+        };
         let mut contents = String::new();
         file.read_to_string(&mut contents)?;
 
@@ -37,11 +68,12 @@ impl Config {
         }
     }
 
-    pub fn get_screen(&self) -> usize {
+    // the story's start screen, overridable with SCREEN_NO when debugging
+    pub fn get_screen(&self, start: usize) -> usize {
         if !self.debug {
-            return 0;
+            return start;
         }
-        get_env("SCREEN_NO", 0 as usize)
+        get_env("SCREEN_NO", start)
     }
 }
 
@@ -0,0 +1,151 @@
+use rand::{RngExt, SeedableRng, rngs::ChaCha12Rng};
+
+// result of a percentile roll against a skill
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    Fumble,
+    Failure,
+    Regular,
+    Hard,
+    Extreme,
+}
+
+impl Outcome {
+    // Call of Cthulhu success levels: 01 and a fifth of the skill are extreme,
+    // half is hard, 100 (96+ when the skill is below 50) is a fumble
+    pub fn of(roll: u8, skill: u8) -> Self {
+        let fumble_from = if skill < 50 { 96 } else { 100 };
+        if roll == 1 || roll <= skill / 5 {
+            Outcome::Extreme
+        } else if roll <= skill / 2 {
+            Outcome::Hard
+        } else if roll <= skill {
+            Outcome::Regular
+        } else if roll >= fumble_from {
+            Outcome::Fumble
+        } else {
+            Outcome::Failure
+        }
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Outcome::Fumble => "Fumble",
+            Outcome::Failure => "Failure",
+            Outcome::Regular => "Success",
+            Outcome::Hard => "Hard success",
+            Outcome::Extreme => "Extreme success",
+        }
+    }
+}
+
+// dice with their own seedable generator so that rolls can be reproduced,
+// the generator of the standard rng but one whose state can be copied
+pub struct Dice {
+    rng: ChaCha12Rng,
+}
+
+// the same dice, a copy rolls what the original would have rolled
+impl Clone for Dice {
+    fn clone(&self) -> Self {
+        let mut rng = ChaCha12Rng::from_seed(self.rng.get_seed());
+        rng.set_stream(self.rng.get_stream());
+        rng.set_word_pos(self.rng.get_word_pos());
+        Self { rng }
+    }
+}
+
+impl Dice {
+    pub fn new(seed: Option<u64>) -> Self {
+        let rng = match seed {
+            Some(seed) => ChaCha12Rng::seed_from_u64(seed),
+            None => rand::make_rng(),
+        };
+        Self { rng }
+    }
+
+    // independent dice derived from this one, reproducible when this one is
+    pub fn fork(&mut self) -> Self {
+        Self::new(Some(self.rng.random()))
+    }
+
+    pub fn d100(&mut self) -> u8 {
+        self.rng.random_range(1..=100)
+    }
+
+    pub fn roll(&mut self, sides: u32) -> u32 {
+        self.rng.random_range(1..=sides)
+    }
+
+    // rolls an expression like `3`, `1d6` or `2d6+1`, None when it is malformed
+    pub fn roll_expr(&mut self, expr: &str) -> Option<u32> {
+        let mut total = 0;
+        for term in expr.split('+').map(str::trim) {
+            total += match term.split_once(['d', 'D']) {
+                Some((count, sides)) => {
+                    let count = if count.is_empty() {
+                        1
+                    } else {
+                        count.parse().ok()?
+                    };
+                    let sides: u32 = sides.parse().ok()?;
+                    if sides == 0 {
+                        return None;
+                    }
+                    (0..count).map(|_| self.roll(sides)).sum::<u32>()
+                }
+                None => term.parse::<u32>().ok()?,
+            };
+        }
+        Some(total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn success_levels() {
+        assert_eq!(Outcome::of(1, 10), Outcome::Extreme);
+        assert_eq!(Outcome::of(15, 75), Outcome::Extreme);
+        assert_eq!(Outcome::of(37, 75), Outcome::Hard);
+        assert_eq!(Outcome::of(75, 75), Outcome::Regular);
+        assert_eq!(Outcome::of(76, 75), Outcome::Failure);
+        assert_eq!(Outcome::of(99, 75), Outcome::Failure);
+        assert_eq!(Outcome::of(100, 75), Outcome::Fumble);
+        assert_eq!(Outcome::of(96, 40), Outcome::Fumble);
+    }
+
+    #[test]
+    fn seeded_rolls_repeat() {
+        let mut a = Dice::new(Some(1926));
+        let mut b = Dice::new(Some(1926));
+        let rolls_a = (0..20).map(|_| a.d100()).collect::<Vec<_>>();
+        let rolls_b = (0..20).map(|_| b.d100()).collect::<Vec<_>>();
+        assert_eq!(rolls_a, rolls_b);
+        assert!(rolls_a.iter().all(|r| (1..=100).contains(r)));
+        assert_eq!(a.fork().d100(), b.fork().d100());
+    }
+
+    #[test]
+    fn copies_roll_the_same() {
+        let mut dice = Dice::new(None);
+        dice.d100();
+        let mut copy = dice.clone();
+        let rolls = (0..20).map(|_| dice.roll(6)).collect::<Vec<_>>();
+        let copied = (0..20).map(|_| copy.roll(6)).collect::<Vec<_>>();
+        assert_eq!(rolls, copied);
+        assert_eq!(dice.fork().d100(), copy.fork().d100());
+    }
+
+    #[test]
+    fn dice_expressions() {
+        let mut dice = Dice::new(Some(7));
+        assert_eq!(dice.roll_expr("3"), Some(3));
+        assert!((1..=6).contains(&dice.roll_expr("1d6").unwrap()));
+        assert!((3..=13).contains(&dice.roll_expr("2d6 + 1").unwrap()));
+        assert_eq!(dice.roll_expr("1d0"), None);
+        assert_eq!(dice.roll_expr("x"), None);
+    }
+}
@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+
+use crate::{engine::story::Story, screens::graph::GameGraph};
+
+// screens of the graph in ascending order, for stable output
+fn sorted_screens(graph: &GameGraph) -> Vec<usize> {
+    let mut screens = graph.graph.keys().copied().collect::<Vec<_>>();
+    screens.sort_unstable();
+    screens
+}
+
+// node label: screen number and the location it announces
+fn node_label(graph: &GameGraph, screen_no: usize) -> String {
+    match graph.locations.get(&screen_no) {
+        Some(location) => format!("{}\n{}", screen_no, location),
+        None => screen_no.to_string(),
+    }
+}
+
+// labelled edges of a screen, one per choice outcome, dashed ones are not chosen by the player
+fn edges(graph: &GameGraph, screen_no: usize) -> Vec<(usize, String, bool)> {
+    let mut edges = Vec::new();
+    for choice in graph.choices.get(&screen_no).into_iter().flatten() {
+        let text = choice.text.trim();
+        edges.push((choice.next, text.to_string(), false));
+        if let Some(check) = &choice.check {
+            let outcomes = [
+                ("failure", Some(check.failure)),
+                ("hard", check.hard),
+                ("extreme", check.extreme),
+                ("fumble", check.fumble),
+            ];
+            for (outcome, target) in outcomes {
+                if let Some(target) = target {
+                    edges.push((target, format!("{} ({})", text, outcome), false));
+                }
+            }
+        }
+    }
+    // the sanity rolls, even where a choice leads to the same screen
+    for (target, reason) in graph.insanity.get(&screen_no).into_iter().flatten() {
+        edges.push((*target, reason.clone(), true));
+    }
+    edges
+}
+
+// fill colour by how often the screen was visited, same buckets as the in-game graph
+fn visit_colour(count: usize) -> &'static str {
+    match count {
+        0 => "#dddddd",
+        1..=2 => "#8fb8ff",
+        3..=5 => "#8fffff",
+        6..=10 => "#8fff8f",
+        11..=20 => "#ffff8f",
+        21..=50 => "#ff8fff",
+        _ => "#ff8f8f",
+    }
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn escape_mermaid(text: &str) -> String {
+    text.replace('"', "#quot;").replace('\n', "<br/>")
+}
+
+// Graphviz DOT, render with `dot -Tsvg`
+pub(crate) fn to_dot(graph: &GameGraph, visits: Option<&HashMap<usize, usize>>) -> String {
+    let mut out = String::from("digraph story {\n  node [shape=box];\n");
+    for screen_no in sorted_screens(graph) {
+        let mut attrs = vec![format!(
+            "label=\"{}\"",
+            escape_dot(&node_label(graph, screen_no))
+        )];
+        let mut styles = Vec::new();
+        if graph.endings.contains(&screen_no) {
+            attrs.push("shape=doubleoctagon".to_string());
+            attrs.push("penwidth=2".to_string());
+            styles.push("bold");
+        }
+        if let Some(visits) = visits {
+            let count = visits.get(&screen_no).copied().unwrap_or(0);
+            attrs.push(format!("fillcolor=\"{}\"", visit_colour(count)));
+            styles.push("filled");
+        }
+        if !styles.is_empty() {
+            attrs.push(format!("style=\"{}\"", styles.join(",")));
+        }
+        out.push_str(&format!("  {} [{}];\n", screen_no, attrs.join(", ")));
+        for (target, label, dashed) in edges(graph, screen_no) {
+            let style = if dashed { ", style=dashed" } else { "" };
+            out.push_str(&format!(
+                "  {} -> {} [label=\"{}\"{}];\n",
+                screen_no,
+                target,
+                escape_dot(&label),
+                style
+            ));
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+// Mermaid flowchart, renders inline in pull requests and docs
+pub(crate) fn to_mermaid(graph: &GameGraph, visits: Option<&HashMap<usize, usize>>) -> String {
+    let mut out = String::from("flowchart TD\n");
+    out.push_str("  classDef ending stroke:#900,stroke-width:3px\n");
+    for screen_no in sorted_screens(graph) {
+        let label = escape_mermaid(&node_label(graph, screen_no));
+        if graph.endings.contains(&screen_no) {
+            out.push_str(&format!("  s{}[[\"{}\"]]:::ending\n", screen_no, label));
+        } else {
+            out.push_str(&format!("  s{}[\"{}\"]\n", screen_no, label));
+        }
+        if let Some(visits) = visits {
+            let count = visits.get(&screen_no).copied().unwrap_or(0);
+            out.push_str(&format!(
+                "  style s{} fill:{}\n",
+                screen_no,
+                visit_colour(count)
+            ));
+        }
+        for (target, label, dashed) in edges(graph, screen_no) {
+            let arrow = if dashed { "-.->" } else { "-->" };
+            out.push_str(&format!(
+                "  s{} {}|\"{}\"| s{}\n",
+                screen_no,
+                arrow,
+                escape_mermaid(&label),
+                target
+            ));
+        }
+    }
+    out
+}
+
+// the whole story structure for external tools
+pub(crate) fn to_json(
+    graph: &GameGraph,
+    story: &Story,
+    visits: Option<&HashMap<usize, usize>>,
+) -> serde_json::Result<String> {
+    let screens = sorted_screens(graph)
+        .into_iter()
+        .map(|screen_no| {
+            let choices = graph
+                .choices
+                .get(&screen_no)
+                .into_iter()
+                .flatten()
+                .map(|choice| {
+                    serde_json::json!({
+                        "text": choice.text.trim(),
+                        "next": choice.next,
+                        "targets": choice.targets(),
+                    })
+                })
+                .collect::<Vec<_>>();
+            let insanity = graph
+                .insanity
+                .get(&screen_no)
+                .into_iter()
+                .flatten()
+                .map(|(next, kind)| serde_json::json!({ "next": next, "kind": kind }))
+                .collect::<Vec<_>>();
+            serde_json::json!({
+                "screen": screen_no,
+                "location": graph.locations.get(&screen_no),
+                "ending": graph.endings.contains(&screen_no),
+                "next": graph.graph[&screen_no],
+                "choices": choices,
+                "insanity": insanity,
+                "visits": visits.map(|v| v.get(&screen_no).copied().unwrap_or(0)),
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::to_string_pretty(&serde_json::json!({
+        "title": story.title,
+        "start": story.start,
+        "metadata": story.metadata,
+        "screens": screens,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use super::*;
+    use crate::screens::play::{Choice, SkillCheck};
+
+    // start screen 0 in Boston with a plain choice, a skill check and a sanity roll
+    // that can send the player where the plain choice leads, ending on screen 3
+    fn graph() -> GameGraph {
+        let mut graph = GameGraph::new();
+        let mut search = Choice::new("search \"the\" desk ", 2);
+        search.check = Some(SkillCheck {
+            skill: "Spot Hidden".to_string(),
+            failure: 3,
+            hard: None,
+            extreme: None,
+            fumble: None,
+        });
+        graph.add_screen(
+            0,
+            &[Choice::new("leave", 1), search],
+            &[(1, "temporary insanity".to_string())],
+        );
+        graph.add_screen(1, &[Choice::new("on", 3)], &[]);
+        graph.add_screen(2, &[Choice::new("on", 3)], &[]);
+        graph.add_screen(3, &[], &[]);
+        graph.endings.insert(3);
+        graph.locations.insert(0, "Boston".to_string());
+        graph
+    }
+
+    fn visits() -> HashMap<usize, usize> {
+        HashMap::from([(0, 1), (1, 4)])
+    }
+
+    #[test]
+    fn dot_export() {
+        let expected = r##"digraph story {
+  node [shape=box];
+  0 [label="0\nBoston", fillcolor="#8fb8ff", style="filled"];
+  0 -> 1 [label="leave"];
+  0 -> 2 [label="search \"the\" desk"];
+  0 -> 3 [label="search \"the\" desk (failure)"];
+  0 -> 1 [label="temporary insanity", style=dashed];
+  1 [label="1", fillcolor="#8fffff", style="filled"];
+  1 -> 3 [label="on"];
+  2 [label="2", fillcolor="#dddddd", style="filled"];
+  2 -> 3 [label="on"];
+  3 [label="3", shape=doubleoctagon, penwidth=2, fillcolor="#dddddd", style="bold,filled"];
+}
+"##;
+        assert_eq!(to_dot(&graph(), Some(&visits())), expected);
+    }
+
+    #[test]
+    fn mermaid_export() {
+        let expected = r##"flowchart TD
+  classDef ending stroke:#900,stroke-width:3px
+  s0["0<br/>Boston"]
+  s0 -->|"leave"| s1
+  s0 -->|"search #quot;the#quot; desk"| s2
+  s0 -->|"search #quot;the#quot; desk (failure)"| s3
+  s0 -.->|"temporary insanity"| s1
+  s1["1"]
+  s1 -->|"on"| s3
+  s2["2"]
+  s2 -->|"on"| s3
+  s3[["3"]]:::ending
+"##;
+        assert_eq!(to_mermaid(&graph(), None), expected);
+    }
+
+    #[test]
+    fn json_export() {
+        let story = Story {
+            title: "Test".to_string(),
+            start: 0,
+            exclude: BTreeSet::new(),
+            screens: vec![0, 1, 2, 3],
+            metadata: BTreeMap::from([("author".to_string(), "HPL".to_string())]),
+        };
+        let exported = to_json(&graph(), &story, Some(&visits())).unwrap();
+        let on = serde_json::json!([{"text": "on", "next": 3, "targets": [3]}]);
+        let expected = serde_json::json!({
+            "title": "Test",
+            "start": 0,
+            "metadata": {"author": "HPL"},
+            "screens": [
+                {
+                    "screen": 0,
+                    "location": "Boston",
+                    "ending": false,
+                    "next": [1, 2, 3, 1],
+                    "choices": [
+                        {"text": "leave", "next": 1, "targets": [1]},
+                        {"text": "search \"the\" desk", "next": 2, "targets": [2, 3]}
+                    ],
+                    "insanity": [{"next": 1, "kind": "temporary insanity"}],
+                    "visits": 1
+                },
+                {"screen": 1, "location": null, "ending": false, "next": [3],
+                 "choices": on, "insanity": [], "visits": 4},
+                {"screen": 2, "location": null, "ending": false, "next": [3],
+                 "choices": on, "insanity": [], "visits": 0},
+                {"screen": 3, "location": null, "ending": true, "next": [],
+                 "choices": [], "insanity": [], "visits": 0}
+            ]
+        });
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&exported).unwrap(),
+            expected
+        );
+    }
+}
@@ -1,40 +1,23 @@
 use image::{Rgb, RgbImage, imageops::FilterType};
 
 use std::{
+    collections::BTreeSet,
     fs::File,
     io::{BufReader, Read},
 };
 
-use crate::{engine::config, screens::play};
+use crate::{
+    engine::{config, gfx::Painter, inventory, story},
+    screens::play,
+};
 
-pub(crate) fn read_bitmap(file_path: &str) -> std::io::Result<(u16, u16, Vec<Vec<(u8, u8, u8)>>)> {
-    let mut file = File::open(file_path)?;
-    let mut width_buf = [0; 2];
-    let mut height_buf = [0; 2];
-    file.read_exact(&mut width_buf)?;
-    file.read_exact(&mut height_buf)?;
+// image rows of RGB pixels, as handed over to the renderers
+pub(crate) type Bitmap = Vec<Vec<(u8, u8, u8)>>;
 
-    let width = u16::from_le_bytes(width_buf);
-    let height = u16::from_le_bytes(height_buf);
+// decoded image: width, height and the pixels in row-major order
+type RawImage = (u16, u16, Vec<(u8, u8, u8)>);
 
-    let mut pixels = Vec::new();
-    let mut pixel_buf = [0; 3];
-    while file.read_exact(&mut pixel_buf).is_ok() {
-        pixels.push((pixel_buf[0], pixel_buf[1], pixel_buf[2]));
-    }
-
-    let mut screen = vec![vec![(0, 0, 0); width as usize]; height as usize];
-    for y in 0..height {
-        for x in 0..width {
-            let pixel = pixels[(y * width + x) as usize];
-            screen[y as usize][x as usize] = pixel;
-        }
-    }
-
-    Ok((width, height, screen))
-}
-
-fn read_png(file_path: &str) -> Result<(u16, u16, Vec<(u8, u8, u8)>), Box<dyn std::error::Error>> {
+fn read_png(file_path: &str) -> Result<RawImage, Box<dyn std::error::Error>> {
     let file = File::open(file_path)?;
     let decoder = png::Decoder::new(BufReader::new(file));
     let mut reader = decoder.read_info()?;
@@ -69,10 +52,9 @@ fn quality_scale_image(
     new_width: u16,
     new_height: u16,
     keep_aspect_ratio: bool,
-) -> Vec<Vec<(u8, u8, u8)>> {
+) -> Bitmap {
     // Convert the input image to an RgbImage
     let mut input_image = RgbImage::new(width as u32, height as u32);
-    let image_len = image.len();
     for (i, pixel) in image.iter().enumerate() {
         let x = (i as u32) % width as u32;
         let y = (i as u32) / width as u32;
@@ -94,7 +76,7 @@ fn quality_scale_image(
     let (mut scaled_width, mut scaled_height) = if new_aspect_ratio > aspect_ratio {
         // Fit to height
         let scaled_width = (new_height as f32 * aspect_ratio).round() as u32;
-        ((2 * scaled_width).min(new_width as u32), new_height as u32)
+        (scaled_width.min(new_width as u32), new_height as u32)
     } else {
         // Fit to width
         let scaled_height = (new_width as f32 / aspect_ratio).round() as u32;
@@ -125,7 +107,7 @@ fn quality_scale_image(
     let y_offset = (new_height as usize - scaled_height as usize) / 2;
     for y in 0..scaled_height {
         for x in 0..scaled_width {
-            let pixel = resized_image.get_pixel(x as u32, y as u32);
+            let pixel = resized_image.get_pixel(x, y);
             scaled[y_offset + y as usize][x_offset + x as usize] = (pixel[0], pixel[1], pixel[2]);
         }
     }
@@ -140,14 +122,14 @@ fn scale_image(
     new_width: u16,
     new_height: u16,
     keep_aspect_ratio: bool,
-) -> Vec<Vec<(u8, u8, u8)>> {
+) -> Bitmap {
     let aspect_ratio = width as f32 / height as f32;
     let new_aspect_ratio = new_width as f32 / new_height as f32;
 
     let (mut scaled_width, mut scaled_height) = if new_aspect_ratio > aspect_ratio {
         // Fit to height
         let scaled_width = (new_height as f32 * aspect_ratio).round() as u16;
-        ((2 * scaled_width).min(new_width), new_height)
+        (scaled_width.min(new_width), new_height)
     } else {
         // Fit to width
         let scaled_height = (new_width as f32 / aspect_ratio).round() as u16;
@@ -190,28 +172,24 @@ pub(crate) fn get_image_names_for_screen(
     }
     let pattern = format!("{}/images/{}_*.png", config.data_path, screen_no);
     let base_path = glob::glob(&pattern)?.collect::<Vec<_>>();
-    for entry in base_path {
-        if let Ok(path) = entry {
-            names.push(path.to_string_lossy().into_owned());
-        }
+    for path in base_path.into_iter().flatten() {
+        names.push(path.to_string_lossy().into_owned());
     }
 
     Ok(names)
 }
 
+// the image scaled to the given size in (about square) pixels, see
+// graphics::ImageBackend::cell_size for how many of them make up a terminal cell
 pub(crate) fn read_image(
     file_path: &str,
     term_width: u16,
     term_height: u16,
     quality_scale: bool,
     keep_aspect_ratio: bool,
-) -> std::io::Result<(u16, u16, Vec<Vec<(u8, u8, u8)>>)> {
-    let (width, height, pixels) = read_png(file_path).map_err(|e| {
-        std::io::Error::new(
-            std::io::ErrorKind::Other,
-            format!("Error reading PNG file: {}", e),
-        )
-    })?;
+) -> std::io::Result<(u16, u16, Bitmap)> {
+    let (width, height, pixels) = read_png(file_path)
+        .map_err(|e| std::io::Error::other(format!("Error reading PNG file: {}", e)))?;
     let scaled = if quality_scale {
         quality_scale_image(
             &pixels,
@@ -264,12 +242,15 @@ pub(crate) fn load_intro_screen_image(
     term_width: u16,
     term_height: u16,
     config: &config::Config,
-) -> Result<Vec<Vec<(u8, u8, u8)>>, Box<dyn std::error::Error>> {
+) -> Result<Bitmap, Box<dyn std::error::Error>> {
     let intro_image = format!("{}/images/intro.png", config.data_path);
+    // backgrounds are drawn in the cells, with as many pixels per cell as the painter uses
+    let painter = Painter::new(config);
+    let (cw, ch) = painter.cell_size();
     let intro_screen = read_image(
         &intro_image,
-        term_width,
-        term_height,
+        term_width * cw,
+        term_height * ch,
         config.scale_quality,
         false,
     )?;
@@ -280,14 +261,67 @@ pub(crate) fn load_achievements_screen_image(
     term_width: u16,
     term_height: u16,
     config: &config::Config,
-) -> Result<Vec<Vec<(u8, u8, u8)>>, Box<dyn std::error::Error>> {
+) -> Result<Bitmap, Box<dyn std::error::Error>> {
     let intro_image = format!("{}/images/achievements.png", config.data_path);
+    // backgrounds are drawn in the cells, with as many pixels per cell as the painter uses
+    let painter = Painter::new(config);
+    let (cw, ch) = painter.cell_size();
     let intro_screen = read_image(
         &intro_image,
-        term_width,
-        term_height,
+        term_width * cw,
+        term_height * ch,
         config.scale_quality,
         false,
     )?;
     Ok(intro_screen.2)
 }
+
+// the item catalog, a story without items simply has no items.json
+pub(crate) fn read_items(config: &config::Config) -> std::io::Result<Vec<inventory::Item>> {
+    let file_path = format!("{}/items.json", config.data_path);
+    let mut file = match File::open(file_path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)?;
+    let items = serde_json::from_str(&contents)?;
+    Ok(items)
+}
+
+// the story manifest, None when the data path has no story.json
+pub(crate) fn read_story(config: &config::Config) -> std::io::Result<Option<story::Story>> {
+    let file_path = format!("{}/story.json", config.data_path);
+    let mut file = match File::open(file_path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)?;
+    let story = serde_json::from_str(&contents)?;
+    Ok(Some(story))
+}
+
+// screen numbers that have a text or an actions file
+pub(crate) fn discover_screens(config: &config::Config) -> std::io::Result<BTreeSet<usize>> {
+    let mut screens = BTreeSet::new();
+    for (dir, extension) in [("text", "txt"), ("actions", "json")] {
+        let dir = format!("{}/{}", config.data_path, dir);
+        let entries = std::fs::read_dir(&dir)
+            .map_err(|e| std::io::Error::new(e.kind(), format!("{}: {}", dir, e)))?;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == extension)
+                && let Some(screen_no) = path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .and_then(|s| s.parse().ok())
+            {
+                screens.insert(screen_no);
+            }
+        }
+    }
+    Ok(screens)
+}
@@ -1,4 +1,4 @@
-use std::{error::Error, io, time::Instant};
+use std::{borrow::Cow, error::Error, io, ops::Range, time::Instant};
 
 use crossterm::{
     event::KeyCode,
@@ -6,12 +6,24 @@ use crossterm::{
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
 use ratatui::{
-    Terminal,
+    Frame, Terminal,
     backend::CrosstermBackend,
+    buffer::Buffer,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Style},
     text::{Line, Span},
-    widgets::{Block, Paragraph},
+    widgets::{Block, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState},
+};
+use unicode_segmentation::UnicodeSegmentation;
+
+use serde::Deserialize;
+
+use crate::engine::{
+    config,
+    fs::Bitmap,
+    graphics::{Graphics, ImageOutput},
+    markup::StyledText,
+    palette::{ColorDepth, Colors, Dither},
 };
 
 // initialize the gfx module
@@ -39,121 +51,639 @@ pub trait ScreenRenderer<T> {
     //
     // # Returns
     //
-    // A vector of colors representing the screen.
+    // Rows of pixels for the image area, two pixel rows per terminal row.
     fn render(&mut self) -> Vec<Vec<(u8, u8, u8)>>;
 
-    fn text(&mut self) -> String;
+    fn text(&mut self) -> StyledText;
+
+    // choices pinned to the bottom of the text window
+    fn choices(&mut self) -> Option<String> {
+        None
+    }
+
+    // scroll position of the text, None when the text does not scroll
+    fn text_scroll(&mut self) -> Option<&mut TextScroll> {
+        None
+    }
+
+    // how the screen is shared between the text and the image, None shows just the image
+    fn split(&self) -> Option<Split>;
+
+    // the terminal changed its size
+    fn resize(&mut self, _width: u16, _height: u16) {}
 
-    fn text_window_sz(&self) -> u16;
+    // status lines shown in a panel below the text window
+    fn side_panel(&self) -> Option<Vec<String>> {
+        None
+    }
 
     fn key_event(&mut self, key_code: KeyCode) -> Option<T>;
 }
 
+// which side of the screen the text window takes
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextPosition {
+    #[default]
+    Left,
+    Right,
+    Bottom,
+}
+
+// layout of a play screen: the text window takes `ratio` of the width (or the height
+// when stacked under the image), the image gets the rest
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Split {
+    pub position: TextPosition,
+    pub ratio: f32,
+}
+
+impl Split {
+    pub(crate) fn new(
+        layout: Option<TextPosition>,
+        text_ratio: Option<f32>,
+        width: u16,
+        height: u16,
+    ) -> Self {
+        let configured = layout.unwrap_or_default();
+        // cells are about twice as tall as wide, a terminal narrower than that is tall
+        // enough to stack the text under the image
+        let position = if width < height.saturating_mul(2) {
+            TextPosition::Bottom
+        } else {
+            configured
+        };
+        // the configured ratio is meant for the configured layout, a width ratio for
+        // the side makes no sense as the height of the text at the bottom
+        let ratio = match text_ratio {
+            Some(ratio) if position == configured => ratio,
+            _ => match position {
+                TextPosition::Bottom => 0.4,
+                _ => 0.25,
+            },
+        };
+        Self {
+            position,
+            ratio: ratio.clamp(0.1, 0.9),
+        }
+    }
+
+    // text and image areas of the given area
+    pub fn areas(&self, area: Rect) -> (Rect, Rect) {
+        let (direction, size) = match self.position {
+            TextPosition::Bottom => (Direction::Vertical, area.height),
+            _ => (Direction::Horizontal, area.width),
+        };
+        let text = Constraint::Length((size as f32 * self.ratio).round() as u16);
+        let constraints = match self.position {
+            TextPosition::Left => [text, Constraint::Min(0)],
+            _ => [Constraint::Min(0), text],
+        };
+        let chunks = Layout::default()
+            .direction(direction)
+            .constraints(constraints)
+            .split(area);
+        match self.position {
+            TextPosition::Left => (chunks[0], chunks[1]),
+            _ => (chunks[1], chunks[0]),
+        }
+    }
+}
+
 // ongoing render loop
 pub fn render<T>(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     renderer: &mut Box<dyn ScreenRenderer<T>>,
+    output: &mut ImageOutput,
 ) -> io::Result<()> {
-    let term_sz = terminal.size().expect("can't get terminal size");
-
     let colors = renderer.render();
-    let wh = renderer.text_window_sz();
-
-    let max_w = term_sz.width / 4; // Split the terminal width into two parts 1:3
+    let split = renderer.split();
 
+    let mut image_area = Rect::default();
     terminal.draw(|f| {
-        let chunks = Layout::default()
-            .direction(Direction::Horizontal) // Vertical split
-            .constraints([Constraint::Length(max_w), Constraint::Min(0)].as_ref())
-            .split(f.area());
-
-        // Render the graphics window on the right
-        let graphics_chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints(
-                colors
-                    .iter()
-                    .map(|_| Constraint::Length(1))
-                    .collect::<Vec<_>>(),
-            )
-            .split(chunks[1]);
-
-        for (i, row) in colors.iter().enumerate() {
-            if i >= graphics_chunks.len() {
-                break; // Prevent rendering beyond the available space
-            }
-            let spans = row
-                .iter()
-                .map(|&(r, g, b)| {
-                    let color = Color::Rgb(r, g, b);
-                    Span::styled("█", Style::default().fg(color))
-                })
-                .collect::<Vec<_>>();
-            let paragraph = Paragraph::new(Line::from(spans));
-            f.render_widget(paragraph, graphics_chunks[i]);
+        let text_area;
+        (text_area, image_area) = match split {
+            Some(split) => split.areas(f.area()),
+            None => (Rect::default(), f.area()),
+        };
+
+        // Render the graphics window
+        output.draw(f.buffer_mut(), image_area, &colors);
+
+        // full-screen image, no text window
+        if text_area.is_empty() {
+            return;
         }
 
-        // Render the text window on the left
-        let bottom_text = renderer.text();
-
         let window_block = Block::default()
             .title("Text Window")
             .style(Style::default().fg(Color::White))
             .border_style(Style::default().fg(Color::Cyan))
             .borders(ratatui::widgets::Borders::ALL);
 
-        let centered_text = Paragraph::new(bottom_text)
-            .style(Style::default().fg(Color::White))
-            .alignment(ratatui::layout::Alignment::Center)
-            .block(window_block);
+        // Render the side panel under the text window, or next to it when the text is
+        // stacked under the image
+        let text_area = if let Some(lines) = renderer.side_panel() {
+            let stacked = split.is_some_and(|s| s.position == TextPosition::Bottom);
+            let (direction, size) = if stacked {
+                let widest = lines.iter().map(|l| l.chars().count()).max();
+                (Direction::Horizontal, widest.unwrap_or(0) as u16 + 2)
+            } else {
+                (Direction::Vertical, lines.len() as u16 + 2)
+            };
+            let text_chunks = Layout::default()
+                .direction(direction)
+                .constraints([Constraint::Min(0), Constraint::Length(size)].as_ref())
+                .split(text_area);
+            let panel = Paragraph::new(lines.into_iter().map(Line::from).collect::<Vec<_>>())
+                .style(Style::default().fg(Color::Gray))
+                .block(
+                    Block::default()
+                        .title("Investigator")
+                        .border_style(Style::default().fg(Color::Cyan))
+                        .borders(ratatui::widgets::Borders::ALL),
+                );
+            f.render_widget(panel, text_chunks[1]);
+            text_chunks[0]
+        } else {
+            text_area
+        };
 
-        f.render_widget(centered_text, chunks[0]);
+        // Render the text window
+        let inner = window_block.inner(text_area);
+        f.render_widget(window_block, text_area);
+        render_text(f, inner, renderer);
     })?;
-    // }
-    Ok(())
+    output.show(terminal.backend_mut(), image_area, &colors)
+}
+
+// upper half block, the upper pixel is the foreground and the lower one the background
+const HALF_BLOCK: &str = "▀";
+
+// characters from dark to bright
+const DENSITY: &[char] = &[' ', '.', ':', '-', '=', '+', '*', '#', '%', '@'];
+
+// a cell is drawn as an edge when its luminance changes by more than this around it
+const EDGE_THRESHOLD: f32 = 384.0;
+
+// glyphs the images are drawn with into the cells of the frame buffer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Glyphs {
+    HalfBlock,               // two pixels per cell in the fore- and background colour
+    Density { edges: bool }, // DENSITY by luminance, optionally "|/-\" along the edges
+    Braille,                 // 2x4 dots per cell, lit when brighter than the image
+}
+
+fn luminance((r, g, b): (u8, u8, u8)) -> f32 {
+    0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32
+}
+
+// the line along an edge, perpendicular to the direction luminance grows in (y grows down)
+fn edge_glyph(gx: f32, gy: f32) -> char {
+    let angle = gy.atan2(gx).to_degrees().rem_euclid(180.0);
+    match angle {
+        a if !(22.5..157.5).contains(&a) => '|',
+        a if a < 67.5 => '/',
+        a if a < 112.5 => '-',
+        _ => '\\',
+    }
+}
+
+// braille dot of the pixel at (x, y) of a 2x4 cell
+fn braille_dot(x: usize, y: usize) -> u32 {
+    match (x, y) {
+        (0, 3) => 0x40,
+        (1, 3) => 0x80,
+        (0, y) => 1 << y,
+        (_, y) => 1 << (y + 3),
+    }
+}
+
+// puts images into the frame buffer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Painter {
+    pub glyphs: Glyphs,
+    pub colors: Colors,
+}
+
+impl Painter {
+    pub(crate) fn new(config: &config::Config) -> Self {
+        let glyphs = match config.graphics {
+            Some(Graphics::Ascii) => Glyphs::Density {
+                edges: config.ascii_edges.unwrap_or(false),
+            },
+            Some(Graphics::Braille) => Glyphs::Braille,
+            _ => Glyphs::HalfBlock,
+        };
+        // characters and dots are drawn by the brightness of the pixels in the terminal's
+        // own colours, a palette would only shift the brightness around
+        let colors = match glyphs {
+            Glyphs::HalfBlock => Colors::new(config),
+            _ => Colors {
+                depth: ColorDepth::TrueColor,
+                dither: Dither::None,
+            },
+        };
+        Self { glyphs, colors }
+    }
+
+    // pixels per terminal cell
+    pub fn cell_size(&self) -> (u16, u16) {
+        match self.glyphs {
+            Glyphs::HalfBlock | Glyphs::Density { .. } => (1, 2),
+            Glyphs::Braille => (2, 4),
+        }
+    }
+
+    // the image reduced to the palette, after any effect was applied to it; images sent
+    // by a graphics protocol keep all their colours
+    fn quantized<'a>(&self, image: &'a Bitmap) -> Cow<'a, Bitmap> {
+        if self.colors.depth == ColorDepth::TrueColor {
+            return Cow::Borrowed(image);
+        }
+        let mut image = image.clone();
+        self.colors.quantize(&mut image);
+        Cow::Owned(image)
+    }
+
+    pub(crate) fn draw(&self, buf: &mut Buffer, area: Rect, image: &Bitmap) {
+        let area = area.intersection(buf.area);
+        let image = &self.quantized(image);
+        match self.glyphs {
+            Glyphs::HalfBlock => self.half_blocks(buf, area, image, true),
+            Glyphs::Density { edges } => density(buf, area, image, edges),
+            Glyphs::Braille => braille(buf, area, image),
+        }
+    }
+
+    // widgets drawn over an image set the foreground colour of the half blocks they leave
+    // blank, this paints those blocks again once the widgets are drawn; the characters
+    // of the other glyphs have no colour to lose
+    pub(crate) fn restore(&self, buf: &mut Buffer, area: Rect, image: &Bitmap) {
+        if self.glyphs == Glyphs::HalfBlock {
+            let image = &self.quantized(image);
+            self.half_blocks(buf, area.intersection(buf.area), image, false);
+        }
+    }
+
+    fn half_blocks(&self, buf: &mut Buffer, area: Rect, image: &Bitmap, overwrite: bool) {
+        for (y, rows) in image.chunks(2).take(area.height as usize).enumerate() {
+            for (x, &upper) in rows[0].iter().take(area.width as usize).enumerate() {
+                let lower = rows
+                    .get(1)
+                    .and_then(|lower| lower.get(x).copied())
+                    .unwrap_or_default();
+                let cell = &mut buf[(area.x + x as u16, area.y + y as u16)];
+                if overwrite || cell.symbol() == HALF_BLOCK {
+                    cell.set_symbol(HALF_BLOCK)
+                        .set_fg(self.colors.color(upper))
+                        .set_bg(self.colors.color(lower));
+                }
+            }
+        }
+    }
+}
+
+// plain characters in the terminal's own colours
+fn put_char(buf: &mut Buffer, x: u16, y: u16, c: char) {
+    buf[(x, y)]
+        .set_char(c)
+        .set_fg(Color::Reset)
+        .set_bg(Color::Reset);
+}
+
+// one character per cell of 1x2 pixels by its luminance, or the direction of an edge
+// running through it
+fn density(buf: &mut Buffer, area: Rect, image: &Bitmap, edges: bool) {
+    let cells = image
+        .chunks(2)
+        .take(area.height as usize)
+        .map(|rows| {
+            (0..rows[0].len().min(area.width as usize))
+                .map(|x| rows.iter().map(|row| luminance(row[x])).sum::<f32>() / rows.len() as f32)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let at = |x: usize, y: usize| cells[y][x];
+    for (y, row) in cells.iter().enumerate() {
+        for (x, &lum) in row.iter().enumerate() {
+            let mut c = DENSITY[(lum / 255.0 * (DENSITY.len() - 1) as f32).round() as usize];
+            // sobel operator over the neighbouring cells
+            if edges && y > 0 && y + 1 < cells.len() && x > 0 && x + 1 < row.len() {
+                let gx = at(x + 1, y - 1) + 2.0 * at(x + 1, y) + at(x + 1, y + 1)
+                    - at(x - 1, y - 1)
+                    - 2.0 * at(x - 1, y)
+                    - at(x - 1, y + 1);
+                let gy = at(x - 1, y + 1) + 2.0 * at(x, y + 1) + at(x + 1, y + 1)
+                    - at(x - 1, y - 1)
+                    - 2.0 * at(x, y - 1)
+                    - at(x + 1, y - 1);
+                if gx.hypot(gy) > EDGE_THRESHOLD {
+                    c = edge_glyph(gx, gy);
+                }
+            }
+            put_char(buf, area.x + x as u16, area.y + y as u16, c);
+        }
+    }
+}
+
+// braille patterns of 2x4 pixels per cell, a dot for each pixel brighter than average
+fn braille(buf: &mut Buffer, area: Rect, image: &Bitmap) {
+    let pixels = image.iter().flatten().count().max(1);
+    let average = image.iter().flatten().map(|&p| luminance(p)).sum::<f32>() / pixels as f32;
+    for (y, rows) in image.chunks(4).take(area.height as usize).enumerate() {
+        let width = (rows[0].len() / 2).min(area.width as usize);
+        for x in 0..width {
+            let mut dots = 0;
+            for (dy, row) in rows.iter().enumerate() {
+                for dx in 0..2 {
+                    if row.get(2 * x + dx).is_some_and(|&p| luminance(p) > average) {
+                        dots |= braille_dot(dx, dy);
+                    }
+                }
+            }
+            let c = char::from_u32(0x2800 + dots).unwrap_or(' ');
+            put_char(buf, area.x + x as u16, area.y + y as u16, c);
+        }
+    }
+}
+
+// scrolled story text with the choices pinned below it
+fn render_text<T>(f: &mut Frame, area: Rect, renderer: &mut Box<dyn ScreenRenderer<T>>) {
+    let width = area.width as usize;
+    let choices = renderer
+        .choices()
+        .map(|c| {
+            wrap(&c, width)
+                .into_iter()
+                .map(|line| Line::from(c[line].to_string()))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length((choices.len() as u16).min(area.height / 2)),
+        ])
+        .split(area);
+
+    // leave a column for the scroll bar
+    let text = renderer.text();
+    // the styles may reach past the part of the text typed out so far
+    let lines = wrap(&text.text, width.saturating_sub(1));
+    let page = chunks[0].height as usize;
+    let offset = match renderer.text_scroll() {
+        Some(scroll) => scroll.update(lines.len(), page),
+        None => 0,
+    };
+    let visible = lines
+        .iter()
+        .skip(offset)
+        .take(page)
+        .map(|line| styled_line(&text, line.clone()))
+        .collect::<Vec<_>>();
+    f.render_widget(
+        Paragraph::new(visible).style(Style::default().fg(Color::White)),
+        chunks[0],
+    );
+    if lines.len() > page {
+        let mut state = ScrollbarState::new(lines.len() - page).position(offset);
+        f.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight),
+            chunks[0],
+            &mut state,
+        );
+    }
+
+    f.render_widget(
+        Paragraph::new(choices).style(Style::default().fg(Color::White)),
+        chunks[1],
+    );
+}
+
+// greedy word wrap into byte ranges of the text, long words are broken and runs of
+// spaces are kept
+pub(crate) fn wrap(text: &str, width: usize) -> Vec<Range<usize>> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    for source in text.split('\n') {
+        let (mut start, mut end, mut len) = (offset, offset, 0);
+        let mut pos = offset;
+        for (i, word) in source.split(' ').enumerate() {
+            let word_len = word.chars().count();
+            let sep = if i == 0 { 0 } else { 1 };
+            pos += sep;
+            if len > 0 && len + sep + word_len > width {
+                lines.push(start..end);
+                (start, end, len) = (pos, pos, 0);
+            } else if i > 0 {
+                end = pos;
+                len += 1;
+            }
+            for (at, c) in word.char_indices() {
+                if len == width {
+                    lines.push(start..end);
+                    (start, len) = (pos + at, 0);
+                }
+                end = pos + at + c.len_utf8();
+                len += 1;
+            }
+            pos += word.len();
+        }
+        lines.push(start..end);
+        offset += source.len() + 1;
+    }
+    lines
+}
+
+// a wrapped line with the spans cut at the style boundaries
+fn styled_line(text: &StyledText, line: Range<usize>) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut pos = line.start;
+    for (range, style) in &text.styles {
+        let (start, end) = (range.start.max(pos), range.end.min(line.end));
+        if start >= end {
+            continue;
+        }
+        if pos < start {
+            spans.push(Span::raw(text.text[pos..start].to_string()));
+        }
+        spans.push(Span::styled(text.text[start..end].to_string(), *style));
+        pos = end;
+    }
+    if pos < line.end {
+        spans.push(Span::raw(text.text[pos..line.end].to_string()));
+    }
+    Line::from(spans)
 }
 
-// TextHelper manages text display
+// joins the hard-wrapped lines of the source text into paragraphs, words hyphenated
+// over a line break ("stá-\ntě") are put back together; a "# " heading line stands on
+// its own and consecutive "> " lines make up a single quoted paragraph
+pub(crate) fn reflow(text: &str) -> String {
+    let mut paragraphs = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for line in text.lines().map(str::trim_end) {
+        let line = line.trim_start();
+        let quote = line.strip_prefix('>').map(str::trim_start);
+        if line.is_empty() || line.starts_with("# ") || quote.is_some() != quoted {
+            if !current.is_empty() {
+                let marker = if quoted { "> " } else { "" };
+                paragraphs.push(format!("{}{}", marker, std::mem::take(&mut current)));
+            }
+            quoted = quote.is_some();
+            if line.starts_with("# ") {
+                paragraphs.push(line.to_string());
+                continue;
+            }
+        }
+        let line = quote.unwrap_or(line);
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(stem) = current.strip_suffix('-')
+            && stem.chars().last().is_some_and(char::is_alphabetic)
+        {
+            // a lowercase continuation means the hyphen only split the word
+            if line.chars().next().is_some_and(char::is_lowercase) {
+                current.truncate(stem.len());
+            }
+        } else if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(line);
+    }
+    if !current.is_empty() {
+        let marker = if quoted { "> " } else { "" };
+        paragraphs.push(format!("{}{}", marker, current));
+    }
+    paragraphs.join("\n\n")
+}
+
+// scroll offset of a text window, follows the end of the text until scrolled back
+pub struct TextScroll {
+    offset: usize,
+    total: usize,
+    page: usize,
+    follow: bool,
+}
+
+impl Default for TextScroll {
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            total: 0,
+            page: 0,
+            follow: true,
+        }
+    }
+}
+
+impl TextScroll {
+    fn max_offset(&self) -> usize {
+        self.total.saturating_sub(self.page)
+    }
+
+    // new text size after layout, returns the offset to draw from
+    fn update(&mut self, total: usize, page: usize) -> usize {
+        self.total = total;
+        self.page = page;
+        if self.follow || self.offset > self.max_offset() {
+            self.offset = self.max_offset();
+        }
+        self.offset
+    }
+
+    pub fn page_up(&mut self) {
+        self.offset = self.offset.saturating_sub(self.page.max(1));
+        self.follow = false;
+    }
+
+    pub fn page_down(&mut self) {
+        self.offset = (self.offset + self.page.max(1)).min(self.max_offset());
+        self.follow = self.offset == self.max_offset();
+    }
+}
+
+// extra pauses after punctuation, in multiples of the time to type one character
+const SENTENCE_PAUSE: f64 = 8.0;
+const CLAUSE_PAUSE: f64 = 3.0;
+
+fn pause_after(grapheme: &str) -> f64 {
+    match grapheme {
+        "." | "!" | "?" | "…" => SENTENCE_PAUSE,
+        "," | ";" | ":" | "—" | "–" => CLAUSE_PAUSE,
+        _ => 0.0,
+    }
+}
+
+// TextHelper manages text display, typing it out one grapheme cluster at a time
 pub struct TextHelper {
     pub text: Option<String>,
     pub text_delay_timer: Instant,
     pub write_speed: f64,
     pub end_of_writing: Option<Instant>,
+    skipped: f64,      // seconds of typing skipped ahead
+    ends: Vec<usize>,  // byte offset just past each grapheme
+    appears: Vec<f64>, // seconds from the start when each grapheme shows up
 }
 
 impl TextHelper {
     pub fn new(write_speed: f64) -> Self {
-        let text_delay_timer = Instant::now();
         Self {
             text: None,
             write_speed,
-            text_delay_timer,
+            text_delay_timer: Instant::now(),
             end_of_writing: None,
+            skipped: 0.0,
+            ends: Vec::new(),
+            appears: Vec::new(),
         }
     }
 
     pub fn with_text(write_speed: f64, text: String) -> Self {
-        let text_delay_timer = Instant::now();
-        Self {
-            text: Some(text),
-            write_speed,
-            text_delay_timer,
-            end_of_writing: None,
-        }
+        let mut helper = Self::new(write_speed);
+        helper.new_text(text);
+        helper
     }
 
     pub fn new_text(&mut self, text: String) {
+        let char_time = 1.0 / self.write_speed;
+        let mut at = 0.0;
+        self.ends.clear();
+        self.appears.clear();
+        for (offset, grapheme) in text.grapheme_indices(true) {
+            at += char_time;
+            self.ends.push(offset + grapheme.len());
+            self.appears.push(at);
+            at += pause_after(grapheme) * char_time;
+        }
         self.text = Some(text);
         self.text_delay_timer = Instant::now();
         self.end_of_writing = None;
+        self.skipped = 0.0;
+    }
+
+    // show the whole text right away
+    pub fn reveal(&mut self) {
+        self.skipped = self.duration();
+    }
+
+    // jump ahead by the given number of seconds of typing
+    pub fn accelerate(&mut self, secs: f64) {
+        self.skipped += secs;
+    }
+
+    pub fn is_typing(&mut self) -> bool {
+        self.text.is_some() && self.text_reached_end().is_none()
     }
 
     pub fn text_reached_end(&mut self) -> Option<Instant> {
-        if self.text.is_none() {
-            return None;
-        }
-        let text = self.text.as_ref().unwrap();
-        let is_end = self.chars_to_show() >= text.len();
+        self.text.as_ref()?;
+        let is_end = self.graphemes_to_show() >= self.ends.len();
         if is_end && self.end_of_writing.is_none() {
             self.end_of_writing = Some(Instant::now());
         }
@@ -161,120 +691,262 @@ impl TextHelper {
     }
 
     pub fn get_text(&mut self) -> Option<String> {
-        if let Some(text) = &self.text {
-            let chars_to_show = self.chars_to_show();
-            if chars_to_show >= text.len() {
-                return Some(text.clone());
-            }
-            // Show only the characters that have been "typed" so far
-            let text: String = text.chars().take(chars_to_show).collect();
-            Some(text)
-        } else {
-            None
-        }
+        let text = self.text.as_ref()?;
+        // Show only the graphemes that have been "typed" so far
+        let shown = self.graphemes_to_show();
+        let end = shown.checked_sub(1).map(|i| self.ends[i]).unwrap_or(0);
+        Some(text[..end].to_string())
     }
 
-    fn chars_to_show(&self) -> usize {
-        let elapsed_time = self.text_delay_timer.elapsed().as_secs_f64();
+    // number of graphemes visible after typing for the given time
+    fn visible_at(&self, secs: f64) -> usize {
+        self.appears.partition_point(|at| *at <= secs)
+    }
 
-        (elapsed_time * self.write_speed) as usize
+    fn graphemes_to_show(&self) -> usize {
+        self.visible_at(self.text_delay_timer.elapsed().as_secs_f64() + self.skipped)
     }
 
-    pub(crate) fn time_to_finish(&self) -> f32 {
-        if self.text.is_none() {
-            return 0.0;
-        }
-        let text = self.text.as_ref().unwrap();
-        let chars_left = text.len() - self.chars_to_show();
-        let time_left = chars_left as f64 / self.write_speed;
-        time_left as f32
+    // time to type the whole text, pauses included
+    pub(crate) fn duration(&self) -> f64 {
+        self.appears.last().copied().unwrap_or(0.0)
     }
 }
 
-pub trait Updater {
-    fn update(&mut self);
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn czech_text_ends_on_the_last_grapheme() {
+        let text = "Příliš žluťoučký kůň".to_string();
+        let graphemes = text.chars().count();
+        let helper = TextHelper::with_text(10.0, text.clone());
+        assert_eq!(helper.ends.len(), graphemes);
+        assert_eq!(helper.ends.last(), Some(&text.len()));
+        assert!((helper.duration() - graphemes as f64 / 10.0).abs() < 1e-9);
+        assert_eq!(helper.visible_at(helper.duration()), graphemes);
+        assert_eq!(helper.visible_at(helper.duration() - 0.05), graphemes - 1);
+    }
 
-pub struct Blink {
-    blink_interval: f64,
-    last_blink_time: Instant,
-    pub is_visible: bool,
-}
+    #[test]
+    fn combining_marks_are_typed_with_their_letter() {
+        // "e" followed by a combining acute accent is a single grapheme
+        let text = "cafe\u{301}!".to_string();
+        let helper = TextHelper::with_text(1.0, text.clone());
+        assert_eq!(helper.ends, vec![1, 2, 3, 6, 7]);
+        let shown = helper.visible_at(4.0);
+        assert_eq!(&text[..helper.ends[shown - 1]], "cafe\u{301}");
+    }
 
-impl Blink {
-    pub fn new(blink_interval: f64) -> Self {
-        Self {
-            blink_interval,
-            last_blink_time: Instant::now(),
-            is_visible: true,
-        }
+    #[test]
+    fn punctuation_pauses_the_typing() {
+        let plain = TextHelper::with_text(10.0, "ab cd".to_string());
+        let paused = TextHelper::with_text(10.0, "ab. d".to_string());
+        let clause = TextHelper::with_text(10.0, "ab, d".to_string());
+        assert!((paused.duration() - plain.duration() - SENTENCE_PAUSE / 10.0).abs() < 1e-9);
+        assert!((clause.duration() - plain.duration() - CLAUSE_PAUSE / 10.0).abs() < 1e-9);
+        // the text after the full stop waits for the pause
+        assert_eq!(paused.visible_at(0.35), 3);
     }
-}
 
-impl Updater for Blink {
-    fn update(&mut self) {
-        if self.last_blink_time.elapsed().as_secs_f64() > self.blink_interval {
-            self.is_visible = !self.is_visible;
-            self.last_blink_time = Instant::now();
-        }
+    #[test]
+    fn reflow_joins_hyphenated_lines() {
+        let text =
+            "Uvod\n\nve stá-\ntě Massachusetts\na Fleur-de-\nLys Building.\n\nDalší odstavec.\n";
+        assert_eq!(
+            reflow(text),
+            "Uvod\n\nve státě Massachusetts a Fleur-de-Lys Building.\n\nDalší odstavec."
+        );
     }
-}
 
-pub struct FirstTime {
-    pub first_time: bool,
-}
+    #[test]
+    fn reflow_keeps_headings_and_quotes_apart() {
+        let text = "# Uvod\nV dopise stojí:\n> Milý synov-\n> če, přijeď.\nKonec.\n";
+        assert_eq!(
+            reflow(text),
+            "# Uvod\n\nV dopise stojí:\n\n> Milý synovče, přijeď.\n\nKonec."
+        );
+    }
 
-impl FirstTime {
-    pub fn new() -> Self {
-        Self { first_time: true }
+    fn wrapped(text: &str, width: usize) -> Vec<&str> {
+        wrap(text, width)
+            .into_iter()
+            .map(|line| &text[line])
+            .collect()
     }
 
-    pub fn isit(&self) -> bool {
-        self.first_time
+    #[test]
+    fn wrap_breaks_on_words() {
+        assert_eq!(
+            wrapped("žluťoučký kůň úpěl", 10),
+            vec!["žluťoučký", "kůň úpěl"]
+        );
+        assert_eq!(
+            wrapped("---> jít\n     dál", 20),
+            vec!["---> jít", "     dál"]
+        );
+        assert_eq!(wrapped("abcdef", 4), vec!["abcd", "ef"]);
     }
-}
 
-impl Updater for FirstTime {
-    fn update(&mut self) {
-        if self.first_time {
-            self.first_time = false;
-        }
+    #[test]
+    fn styled_lines_split_at_style_boundaries() {
+        let text = crate::engine::markup::parse("a **bold** word");
+        let lines = wrap(&text.text, 6);
+        let line = styled_line(&text, lines[0].clone());
+        assert_eq!(line.spans.len(), 2);
+        assert_eq!(line.spans[0].content, "a ");
+        assert_eq!(line.spans[1].content, "bold");
+        let line = styled_line(&text, lines[1].clone());
+        assert_eq!(line.spans[0].content, "word");
     }
-}
 
-// timer, can be periodical or one-time
-pub struct Timer {
-    start_time: Option<Instant>,
-    trigger_time: f64,
-    periodical: bool,
-}
-impl Timer {
-    pub fn new(trigger_time: f64, periodical: bool) -> Self {
-        Self {
-            trigger_time,
-            start_time: None,
-            periodical,
-        }
+    #[test]
+    fn density_follows_luminance_and_edges() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 4, 3));
+        let area = buf.area;
+        let dark_to_light = vec![vec![(0, 0, 0), (128, 128, 128), (255, 255, 255)]; 2];
+        density(&mut buf, area, &dark_to_light, false);
+        assert_eq!(buf[(0, 0)].symbol(), " ");
+        assert_eq!(buf[(1, 0)].symbol(), "+");
+        assert_eq!(buf[(2, 0)].symbol(), "@");
+        // a dark left half next to a bright right half is a vertical edge
+        let halves = vec![vec![(0, 0, 0), (0, 0, 0), (255, 255, 255), (255, 255, 255)]; 6];
+        density(&mut buf, area, &halves, true);
+        assert_eq!(buf[(1, 1)].symbol(), "|");
+        assert_eq!(edge_glyph(0.0, 1.0), '-');
+        assert_eq!(edge_glyph(1.0, 1.0), '/');
+        assert_eq!(edge_glyph(-1.0, 1.0), '\\');
     }
 
-    pub fn reset(&mut self) {
-        self.start_time = Some(Instant::now());
+    #[test]
+    fn braille_lights_the_bright_pixels() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 1, 1));
+        let area = buf.area;
+        let (on, off) = ((255, 255, 255), (0, 0, 0));
+        // the left column and the bottom right pixel
+        let image = vec![vec![on, off], vec![on, off], vec![on, off], vec![on, on]];
+        braille(&mut buf, area, &image);
+        assert_eq!(buf[(0, 0)].symbol(), "\u{28c7}");
     }
 
-    pub fn is_trigger(&mut self) -> bool {
-        if let Some(start_time) = self.start_time {
-            let elapsed_time = start_time.elapsed().as_secs_f64();
-            if self.periodical {
-                if elapsed_time > self.trigger_time {
-                    self.start_time = Some(Instant::now());
-                    return true;
-                }
-            } else {
-                if elapsed_time > self.trigger_time {
-                    return true;
-                }
-            }
+    #[test]
+    fn revealed_text_is_complete() {
+        let text = "Ze čtení tě náhle vytrhne zaklepání na dveře.".to_string();
+        let mut helper = TextHelper::with_text(1.0, text.clone());
+        assert!(helper.is_typing());
+        helper.reveal();
+        assert_eq!(helper.get_text(), Some(text));
+        assert!(helper.text_reached_end().is_some());
+        assert!(!helper.is_typing());
+    }
+
+    #[test]
+    fn the_text_window_follows_the_typing_until_scrolled_back() {
+        let mut scroll = TextScroll::default();
+        assert_eq!(scroll.update(5, 10), 0, "fits the window");
+        assert_eq!(scroll.update(25, 10), 15, "follows the newest line");
+        scroll.page_up();
+        assert_eq!(scroll.update(30, 10), 5, "stays where the player scrolled");
+        scroll.page_down();
+        scroll.page_down();
+        assert_eq!(scroll.update(32, 10), 22, "follows again from the bottom");
+        assert_eq!(scroll.update(12, 10), 2, "a shorter text pulls it back");
+    }
+
+    #[test]
+    fn fast_forward_skips_ahead_by_typing_time() {
+        let mut helper = TextHelper::with_text(1.0, "abcdef".to_string());
+        helper.accelerate(3.0);
+        assert_eq!(helper.get_text().as_deref(), Some("abc"));
+        assert!(helper.is_typing());
+        helper.accelerate(100.0);
+        assert_eq!(helper.get_text().as_deref(), Some("abcdef"));
+        assert!(!helper.is_typing());
+    }
+
+    #[test]
+    fn a_side_ratio_is_not_used_for_the_text_at_the_bottom() {
+        let side = Split::new(Some(TextPosition::Left), Some(0.7), 200, 50);
+        assert_eq!((side.position, side.ratio), (TextPosition::Left, 0.7));
+        // too narrow for the text at the side
+        let narrow = Split::new(Some(TextPosition::Left), Some(0.7), 80, 50);
+        assert_eq!((narrow.position, narrow.ratio), (TextPosition::Bottom, 0.4));
+        let bottom = Split::new(Some(TextPosition::Bottom), Some(0.3), 80, 50);
+        assert_eq!((bottom.position, bottom.ratio), (TextPosition::Bottom, 0.3));
+    }
+
+    #[test]
+    fn half_blocks_show_two_pixels_per_cell() {
+        let painter = Painter {
+            glyphs: Glyphs::HalfBlock,
+            colors: Colors {
+                depth: ColorDepth::TrueColor,
+                dither: Dither::None,
+            },
+        };
+        let (red, blue) = ((255, 0, 0), (0, 0, 255));
+        // an odd last row leaves the lower halves black
+        let image = vec![vec![red, blue], vec![blue, red], vec![red, red]];
+        let area = Rect::new(0, 0, 2, 2);
+        let mut buf = Buffer::empty(area);
+        painter.draw(&mut buf, area, &image);
+        let cell = |x: u16, y: u16| {
+            let cell = &buf[(x, y)];
+            (cell.symbol().to_string(), cell.fg, cell.bg)
+        };
+        let (r, b, k) = (
+            Color::Rgb(255, 0, 0),
+            Color::Rgb(0, 0, 255),
+            Color::Rgb(0, 0, 0),
+        );
+        assert_eq!(cell(0, 0), (HALF_BLOCK.to_string(), r, b));
+        assert_eq!(cell(1, 0), (HALF_BLOCK.to_string(), b, r));
+        assert_eq!(cell(0, 1), (HALF_BLOCK.to_string(), r, k));
+    }
+
+    #[test]
+    fn half_blocks_are_dithered_as_drawn() {
+        let colors = Colors {
+            depth: ColorDepth::Ansi16,
+            dither: Dither::FloydSteinberg,
+        };
+        let painter = Painter {
+            glyphs: Glyphs::HalfBlock,
+            colors,
+        };
+        // halfway between black and grey
+        let image = vec![vec![(64, 64, 64); 4]; 2];
+        let area = Rect::new(0, 0, 4, 1);
+        let mut buf = Buffer::empty(area);
+        painter.draw(&mut buf, area, &image);
+
+        let mut dithered = image.clone();
+        colors.quantize(&mut dithered);
+        let drawn = (0..4).map(|x| buf[(x, 0)].fg).collect::<Vec<_>>();
+        let expected = dithered[0]
+            .iter()
+            .map(|p| colors.color(*p))
+            .collect::<Vec<_>>();
+        assert_eq!(drawn, expected);
+        assert!(drawn.iter().any(|c| *c != colors.color(image[0][0])));
+    }
+
+    #[test]
+    fn characters_and_dots_keep_the_brightness() {
+        for graphics in [Graphics::Ascii, Graphics::Braille] {
+            let config = config::Config {
+                graphics: Some(graphics),
+                colors: Some(ColorDepth::Ansi16),
+                dither: Some(Dither::Ordered),
+                ..config::Config::default()
+            };
+            assert_eq!(Painter::new(&config).colors.depth, ColorDepth::TrueColor);
         }
-        return false;
+        let config = config::Config {
+            colors: Some(ColorDepth::Ansi16),
+            ..config::Config::default()
+        };
+        assert_eq!(Painter::new(&config).colors.depth, ColorDepth::Ansi16);
     }
 }
@@ -0,0 +1,411 @@
+use std::{
+    collections::BTreeMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+use crossterm::{cursor::MoveTo, queue, terminal};
+use ratatui::{
+    buffer::{Buffer, CellDiffOption},
+    layout::Rect,
+};
+use serde::Deserialize;
+
+use crate::engine::{config, fs::Bitmap, gfx::Painter};
+
+// how the illustrations get to the terminal
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Graphics {
+    Block,   // coloured half blocks, works everywhere
+    Sixel,   // DEC sixel graphics (xterm -ti vt340, foot, mlterm, WezTerm, ...)
+    Kitty,   // kitty graphics protocol (kitty, WezTerm, Ghostty)
+    Ascii,   // characters by brightness, for monochrome terminals and logs
+    Braille, // braille dots, twice the resolution of the characters
+}
+
+impl Graphics {
+    // guess from the environment, there is no reliable way to ask every terminal
+    pub(crate) fn detect() -> Self {
+        let var = |name| std::env::var(name).unwrap_or_default();
+        let (term, program) = (var("TERM"), var("TERM_PROGRAM"));
+        if !var("KITTY_WINDOW_ID").is_empty()
+            || term.contains("kitty")
+            || term.contains("ghostty")
+            || program == "WezTerm"
+            || program == "ghostty"
+        {
+            Graphics::Kitty
+        } else if term.contains("sixel")
+            || term.starts_with("foot")
+            || term.starts_with("mlterm")
+            || program == "iTerm.app"
+        {
+            Graphics::Sixel
+        } else {
+            Graphics::Block
+        }
+    }
+}
+
+// a way of putting images on the screen
+pub trait ImageBackend {
+    // pixels per terminal cell the images are scaled to
+    fn cell_size(&self) -> (u16, u16);
+
+    // draws into the frame buffer, the graphics protocols only reserve the cells
+    fn draw(&self, buf: &mut Buffer, area: Rect, _image: &Bitmap) {
+        for position in area.intersection(buf.area).positions() {
+            buf[position].set_diff_option(CellDiffOption::Skip);
+        }
+    }
+
+    // escape sequence showing the image over the area from its top left corner, None
+    // when the image is drawn into the frame buffer
+    fn encode(&self, _area: Rect, _image: &Bitmap) -> Option<String> {
+        None
+    }
+
+    // escape sequence removing the image from the screen
+    fn clear(&self) -> Option<String> {
+        None
+    }
+}
+
+// half blocks, characters or braille dots in the cells of the frame buffer
+pub struct BlockBackend {
+    painter: Painter,
+}
+
+impl ImageBackend for BlockBackend {
+    fn cell_size(&self) -> (u16, u16) {
+        self.painter.cell_size()
+    }
+
+    fn draw(&self, buf: &mut Buffer, area: Rect, image: &Bitmap) {
+        self.painter.draw(buf, area, image);
+    }
+}
+
+pub struct SixelBackend {
+    cell: (u16, u16),
+}
+
+impl ImageBackend for SixelBackend {
+    fn cell_size(&self) -> (u16, u16) {
+        self.cell
+    }
+
+    fn encode(&self, _area: Rect, image: &Bitmap) -> Option<String> {
+        // a band reaching below the last row would scroll the whole screen
+        let rows = image.len() - image.len() % 6;
+        Some(encode_sixel(&image[..rows]))
+    }
+}
+
+pub struct KittyBackend {
+    cell: (u16, u16),
+}
+
+impl ImageBackend for KittyBackend {
+    fn cell_size(&self) -> (u16, u16) {
+        self.cell
+    }
+
+    fn encode(&self, area: Rect, image: &Bitmap) -> Option<String> {
+        Some(encode_kitty(image, area.width, area.height))
+    }
+
+    fn clear(&self) -> Option<String> {
+        Some(format!("\x1b_Ga=d,d=I,i={},q=2\x1b\\", KITTY_IMAGE_ID))
+    }
+}
+
+// size of a terminal cell in pixels, a common guess when the terminal does not tell
+fn cell_pixels() -> (u16, u16) {
+    match terminal::window_size() {
+        Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => {
+            (size.width / size.columns, size.height / size.rows)
+        }
+        _ => (10, 20),
+    }
+}
+
+// the configured backend, or the one the terminal seems to support
+pub(crate) fn backend(config: &config::Config) -> Box<dyn ImageBackend> {
+    match config.graphics.unwrap_or_else(Graphics::detect) {
+        Graphics::Block | Graphics::Ascii | Graphics::Braille => Box::new(BlockBackend {
+            painter: Painter::new(config),
+        }),
+        Graphics::Sixel => Box::new(SixelBackend {
+            cell: cell_pixels(),
+        }),
+        Graphics::Kitty => Box::new(KittyBackend {
+            cell: cell_pixels(),
+        }),
+    }
+}
+
+// a graphics protocol image is sent at most this often, e.g. while the madness wobbles it
+const MIN_RESEND: Duration = Duration::from_millis(100);
+
+// puts the play screen's image on the terminal, re-sending it only when it changed
+pub struct ImageOutput {
+    backend: Box<dyn ImageBackend>,
+    shown: Option<(Rect, u64)>,
+    sent: Instant,
+}
+
+impl ImageOutput {
+    pub fn new(backend: Box<dyn ImageBackend>) -> Self {
+        Self {
+            backend,
+            shown: None,
+            sent: Instant::now(),
+        }
+    }
+
+    pub fn cell_size(&self) -> (u16, u16) {
+        self.backend.cell_size()
+    }
+
+    pub fn draw(&self, buf: &mut Buffer, area: Rect, image: &Bitmap) {
+        self.backend.draw(buf, area, image);
+    }
+
+    // after the frame is drawn, sends the image if the backend draws it by itself
+    pub fn show<W: Write>(&mut self, out: &mut W, area: Rect, image: &Bitmap) -> io::Result<()> {
+        let mut hasher = DefaultHasher::new();
+        image.hash(&mut hasher);
+        let shown = (area, hasher.finish());
+        if self.shown == Some(shown)
+            || self.shown.is_some_and(|(a, _)| a == area) && self.sent.elapsed() < MIN_RESEND
+        {
+            return Ok(());
+        }
+        let Some(encoded) = self.backend.encode(area, image) else {
+            return Ok(());
+        };
+        queue!(out, MoveTo(area.x, area.y))?;
+        out.write_all(encoded.as_bytes())?;
+        out.flush()?;
+        self.shown = Some(shown);
+        self.sent = Instant::now();
+        Ok(())
+    }
+
+    // takes the image off the screen, e.g. when leaving the play screen; true when
+    // the backend can't remove it and the whole terminal has to be drawn again, sixel
+    // images stay until something is drawn over them
+    pub fn clear<W: Write>(&mut self, out: &mut W) -> io::Result<bool> {
+        if self.shown.take().is_none() {
+            return Ok(false);
+        }
+        let Some(clear) = self.backend.clear() else {
+            return Ok(true);
+        };
+        out.write_all(clear.as_bytes())?;
+        out.flush()?;
+        Ok(false)
+    }
+}
+
+// sixel colour registers: a 6x6x6 colour cube, levels 0..=5 per channel
+fn sixel_register((r, g, b): (u8, u8, u8)) -> usize {
+    let level = |c: u8| (c as usize * 5 + 127) / 255;
+    level(r) * 36 + level(g) * 6 + level(b)
+}
+
+fn push_run(out: &mut String, c: char, count: usize) {
+    if count > 3 {
+        out.push_str(&format!("!{}{}", count, c));
+    } else {
+        out.extend(std::iter::repeat_n(c, count));
+    }
+}
+
+// DEC sixel image with square pixels, bands of six rows painted colour by colour
+pub(crate) fn encode_sixel(image: &[Vec<(u8, u8, u8)>]) -> String {
+    let height = image.len();
+    let width = image.first().map_or(0, Vec::len);
+    let mut out = format!("\x1bPq\"1;1;{};{}", width, height);
+
+    let registers = image
+        .iter()
+        .map(|row| row.iter().map(|&p| sixel_register(p)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let mut used = registers.iter().flatten().copied().collect::<Vec<_>>();
+    used.sort_unstable();
+    used.dedup();
+    for &register in &used {
+        let percent = |level: usize| level * 20;
+        out.push_str(&format!(
+            "#{};2;{};{};{}",
+            register,
+            percent(register / 36),
+            percent(register / 6 % 6),
+            percent(register % 6)
+        ));
+    }
+
+    for (band_no, band) in registers.chunks(6).enumerate() {
+        if band_no > 0 {
+            out.push('-');
+        }
+        // the six bits of each column, per colour
+        let mut columns = BTreeMap::<usize, Vec<u8>>::new();
+        for (bit, row) in band.iter().enumerate() {
+            for (x, &register) in row.iter().enumerate() {
+                columns.entry(register).or_insert_with(|| vec![0; width])[x] |= 1 << bit;
+            }
+        }
+        for (i, (register, bits)) in columns.iter().enumerate() {
+            if i > 0 {
+                out.push('$');
+            }
+            out.push_str(&format!("#{}", register));
+            // the empty columns at the end need not be sent
+            let len = bits
+                .iter()
+                .rposition(|&b| b != 0)
+                .map_or(0, |last| last + 1);
+            let mut run = (' ', 0);
+            for &b in &bits[..len] {
+                let c = (63 + b) as char;
+                if c == run.0 {
+                    run.1 += 1;
+                } else {
+                    push_run(&mut out, run.0, run.1);
+                    run = (c, 1);
+                }
+            }
+            push_run(&mut out, run.0, run.1);
+        }
+    }
+    out.push_str("\x1b\\");
+    out
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+// the same image id is reused, sending a new image replaces the old one
+const KITTY_IMAGE_ID: u32 = 1;
+// the protocol limits the payload of one escape sequence
+const KITTY_CHUNK: usize = 4096;
+
+// kitty graphics protocol: raw RGB pixels scaled into `columns` x `rows` cells, the
+// cursor stays where it is
+pub(crate) fn encode_kitty(image: &Bitmap, columns: u16, rows: u16) -> String {
+    let height = image.len();
+    let width = image.first().map_or(0, Vec::len);
+    let pixels = image
+        .iter()
+        .flatten()
+        .flat_map(|&(r, g, b)| [r, g, b])
+        .collect::<Vec<_>>();
+    let payload = base64(&pixels);
+    let chunks = payload.as_bytes().chunks(KITTY_CHUNK).collect::<Vec<_>>();
+    let mut out = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = if i + 1 < chunks.len() { 1 } else { 0 };
+        if i == 0 {
+            out.push_str(&format!(
+                "\x1b_Ga=T,f=24,i={},p=1,s={},v={},c={},r={},C=1,q=2,m={};",
+                KITTY_IMAGE_ID, width, height, columns, rows, more
+            ));
+        } else {
+            out.push_str(&format!("\x1b_Gm={};", more));
+        }
+        // base64 is plain ascii
+        out.push_str(std::str::from_utf8(chunk).unwrap_or_default());
+        out.push_str("\x1b\\");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: (u8, u8, u8) = (255, 0, 0);
+    const BLUE: (u8, u8, u8) = (0, 0, 255);
+    const BLACK: (u8, u8, u8) = (0, 0, 0);
+
+    #[test]
+    fn sixel_paints_each_colour_of_a_band() {
+        let image = vec![vec![RED, RED, BLUE], vec![RED, BLACK, BLUE]];
+        assert_eq!(
+            encode_sixel(&image),
+            "\x1bPq\"1;1;3;2#0;2;0;0;0#5;2;0;0;100#180;2;100;0;0#0?A$#5??B$#180B@\x1b\\"
+        );
+    }
+
+    #[test]
+    fn sixel_compresses_runs_and_starts_new_bands() {
+        let image = vec![vec![BLUE; 5]; 7];
+        assert_eq!(
+            encode_sixel(&image),
+            "\x1bPq\"1;1;5;7#5;2;0;0;100#5!5~-#5!5@\x1b\\"
+        );
+    }
+
+    #[test]
+    fn base64_pads_the_last_group() {
+        assert_eq!(base64(b"Man"), "TWFu");
+        assert_eq!(base64(b"Ma"), "TWE=");
+        assert_eq!(base64(b"M"), "TQ==");
+    }
+
+    #[test]
+    fn kitty_sends_rgb_pixels_in_chunks() {
+        assert_eq!(
+            encode_kitty(&vec![vec![RED]], 1, 1),
+            "\x1b_Ga=T,f=24,i=1,p=1,s=1,v=1,c=1,r=1,C=1,q=2,m=0;/wAA\x1b\\"
+        );
+        // 1024 pixels of 3 bytes are 4096 base64 characters, one more needs a second chunk
+        let encoded = encode_kitty(&vec![vec![RED; 1025]], 10, 1);
+        assert!(encoded.contains("m=1;"));
+        assert!(encoded.ends_with("\x1b_Gm=0;/wAA\x1b\\"));
+    }
+
+    #[test]
+    fn images_that_cant_be_deleted_ask_for_a_repaint() {
+        let image = vec![vec![RED; 6]; 6];
+        let area = Rect::new(0, 0, 1, 1);
+        let mut out = Vec::new();
+
+        let mut sixel = ImageOutput::new(Box::new(SixelBackend { cell: (6, 6) }));
+        assert!(!sixel.clear(&mut out).unwrap(), "nothing shown yet");
+        sixel.show(&mut out, area, &image).unwrap();
+        assert!(sixel.clear(&mut out).unwrap());
+        assert!(!sixel.clear(&mut out).unwrap(), "already gone");
+
+        let mut kitty = ImageOutput::new(Box::new(KittyBackend { cell: (6, 6) }));
+        kitty.show(&mut out, area, &image).unwrap();
+        out.clear();
+        assert!(!kitty.clear(&mut out).unwrap());
+        assert_eq!(
+            out,
+            KittyBackend { cell: (6, 6) }.clear().unwrap().as_bytes()
+        );
+    }
+}
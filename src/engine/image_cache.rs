@@ -0,0 +1,161 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+    sync::{
+        Arc, Mutex,
+        mpsc::{self, Sender},
+    },
+    thread,
+};
+
+use crate::engine::fs::{self, Bitmap};
+
+// an image scaled to fit the given size, with the cheap or the quality scaler
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Key {
+    path: String,
+    width: u16,
+    height: u16,
+    quality: bool,
+}
+
+impl Key {
+    fn load(&self) -> io::Result<Bitmap> {
+        let (_, _, image) =
+            fs::read_image(&self.path, self.width, self.height, self.quality, true)?;
+        Ok(image)
+    }
+}
+
+// the images and the ones the current screen can still ask for
+#[derive(Default)]
+struct Store {
+    images: HashMap<Key, Arc<Bitmap>>,
+    wanted: HashSet<Key>,
+}
+
+type Images = Arc<Mutex<Store>>;
+
+// decoded and scaled images, shared with a worker thread that prepares the images of
+// the screens the player can go to next; the worker stops with the last clone; only
+// the images of the current screen and of the screens it leads to are kept
+#[derive(Clone)]
+pub struct ImageCache {
+    images: Images,
+    jobs: Sender<Vec<Key>>,
+    cell: (u16, u16), // pixels per terminal cell
+}
+
+impl ImageCache {
+    pub fn new(cell: (u16, u16)) -> Self {
+        let images = Images::default();
+        let (jobs, queue) = mpsc::channel::<Vec<Key>>();
+        let worker = Arc::clone(&images);
+        thread::spawn(move || {
+            for keys in queue {
+                for key in keys {
+                    // skip what is cached and what a newer screen no longer wants
+                    let store = worker.lock().unwrap();
+                    if store.images.contains_key(&key) || !store.wanted.contains(&key) {
+                        continue;
+                    }
+                    drop(store);
+                    // the lock is not held while decoding, a missing image shows up
+                    // as an error once the screen asks for it
+                    if let Ok(image) = key.load() {
+                        let mut store = worker.lock().unwrap();
+                        if store.wanted.contains(&key) {
+                            store.images.insert(key, Arc::new(image));
+                        }
+                    }
+                }
+            }
+        });
+        Self { images, jobs, cell }
+    }
+
+    pub fn cell_size(&self) -> (u16, u16) {
+        self.cell
+    }
+
+    // the image fitted into `width` x `height` terminal cells
+    pub fn get(
+        &self,
+        path: &str,
+        width: u16,
+        height: u16,
+        quality: bool,
+    ) -> io::Result<Arc<Bitmap>> {
+        let key = Key {
+            path: path.to_string(),
+            width: width * self.cell.0,
+            height: height * self.cell.1,
+            quality,
+        };
+        if let Some(image) = self.images.lock().unwrap().images.get(&key) {
+            return Ok(Arc::clone(image));
+        }
+        let image = Arc::new(key.load()?);
+        self.images
+            .lock()
+            .unwrap()
+            .images
+            .insert(key, Arc::clone(&image));
+        Ok(image)
+    }
+
+    // decode and scale the images in the background, the images of a new screen and
+    // the screens it leads to; all the others are dropped
+    pub fn preload(&self, paths: Vec<String>, width: u16, height: u16, quality: bool) {
+        let keys = paths
+            .into_iter()
+            .map(|path| Key {
+                path,
+                width: width * self.cell.0,
+                height: height * self.cell.1,
+                quality,
+            })
+            .collect::<Vec<_>>();
+        let mut store = self.images.lock().unwrap();
+        store.wanted = keys.iter().cloned().collect();
+        let Store { images, wanted } = &mut *store;
+        images.retain(|key, _| wanted.contains(key));
+        drop(store);
+        // the worker only goes away together with the cache
+        let _ = self.jobs.send(keys);
+    }
+
+    // forget all images, e.g. when the terminal size changes
+    pub fn clear(&self) {
+        self.images.lock().unwrap().images.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached(cache: &ImageCache) -> Vec<String> {
+        let store = cache.images.lock().unwrap();
+        let mut paths = store
+            .images
+            .keys()
+            .map(|k| k.path.clone())
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn images_of_screens_left_behind_are_dropped() {
+        let cache = ImageCache::new((1, 2));
+        let (old, new) = ("assets/images/1.png", "assets/images/17.png");
+        cache.preload(vec![old.to_string()], 8, 4, false);
+        cache.get(old, 8, 4, false).unwrap();
+        assert_eq!(cached(&cache), [old]);
+
+        cache.preload(vec![new.to_string()], 8, 4, false);
+        cache.get(new, 8, 4, false).unwrap();
+        assert_eq!(cached(&cache), [new]);
+    }
+}
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+
+// item or clue the player can collect, described in items.json in the data path
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct Item {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) description: String,
+    pub(crate) image: Option<String>, // file name in the images directory
+    #[serde(default)]
+    pub(crate) clue: bool, // clues go to the notebook, the rest to the inventory
+}
+
+// ids of the collected items, in the order they were found
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Inventory {
+    items: Vec<String>,
+}
+
+impl Inventory {
+    pub fn has(&self, id: &str) -> bool {
+        self.items.iter().any(|i| i == id)
+    }
+
+    pub fn give(&mut self, id: &str) {
+        if !self.has(id) {
+            self.items.push(id.to_string());
+        }
+    }
+
+    pub fn take(&mut self, id: &str) {
+        self.items.retain(|i| i != id);
+    }
+
+    // the collected items looked up in the item catalog, unknown ids are skipped
+    pub(crate) fn resolve(&self, catalog: &[Item]) -> Vec<Item> {
+        self.items
+            .iter()
+            .filter_map(|id| catalog.iter().find(|item| &item.id == id).cloned())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: &str) -> Item {
+        Item {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            image: None,
+            clue: false,
+        }
+    }
+
+    #[test]
+    fn items_are_listed_in_the_order_they_were_found() {
+        let catalog = [item("lamp"), item("revolver"), item("statue")];
+        let mut inventory = Inventory::default();
+        inventory.give("statue");
+        inventory.give("unknown");
+        inventory.give("lamp");
+        inventory.give("statue");
+        let ids = |inventory: &Inventory| {
+            inventory
+                .resolve(&catalog)
+                .into_iter()
+                .map(|i| i.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(&inventory), ["statue", "lamp"]);
+        inventory.take("statue");
+        assert_eq!(ids(&inventory), ["lamp"]);
+    }
+}
@@ -0,0 +1,278 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+// points spread over the characteristics in point-buy creation
+pub(crate) const CHARACTERISTIC_POINTS: u32 = 460;
+pub(crate) const CHARACTERISTIC_MIN: u8 = 15;
+pub(crate) const CHARACTERISTIC_MAX: u8 = 90;
+pub(crate) const SKILL_MAX: u8 = 90;
+
+pub(crate) const CHARACTERISTICS: [&str; 8] =
+    ["STR", "CON", "SIZ", "DEX", "APP", "INT", "POW", "EDU"];
+
+// skills known to the game with their base chance
+pub(crate) const BASE_SKILLS: [(&str, u8); 16] = [
+    ("Anthropology", 1),
+    ("Archaeology", 1),
+    ("Climb", 20),
+    ("Cthulhu Mythos", 0),
+    ("First Aid", 30),
+    ("History", 5),
+    ("Library Use", 20),
+    ("Listen", 20),
+    ("Navigate", 10),
+    ("Occult", 5),
+    ("Persuade", 10),
+    ("Psychology", 10),
+    ("Spot Hidden", 25),
+    ("Stealth", 20),
+    ("Swim", 20),
+    ("Dodge", 0), // half of DEX, see `base_skill`
+];
+
+// Call of Cthulhu investigator sheet
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Investigator {
+    pub(crate) name: String,
+    pub(crate) occupation: String,
+    pub(crate) characteristics: BTreeMap<String, u8>,
+    pub(crate) hp: i32,
+    pub(crate) max_hp: i32,
+    pub(crate) sanity: i32,
+    pub(crate) max_sanity: i32,
+    pub(crate) luck: i32,
+    pub(crate) skills: BTreeMap<String, u8>,
+}
+
+impl Default for Investigator {
+    fn default() -> Self {
+        Self::thurston()
+    }
+}
+
+impl Investigator {
+    // the narrator of the story, ready to play
+    pub fn thurston() -> Self {
+        let mut investigator = Self::with_characteristics(
+            "Francis Wayland Thurston",
+            "Professor of Anthropology",
+            [40, 50, 55, 45, 50, 75, 60, 85],
+        );
+        for (skill, value) in [
+            ("Anthropology", 70),
+            ("Archaeology", 50),
+            ("History", 60),
+            ("Library Use", 75),
+            ("Occult", 35),
+            ("Persuade", 40),
+            ("Psychology", 45),
+            ("Spot Hidden", 50),
+            ("Navigate", 30),
+        ] {
+            investigator.skills.insert(skill.to_string(), value);
+        }
+        investigator.luck = 55;
+        investigator
+    }
+
+    // fresh investigator for point-buy, all characteristics at the average
+    pub fn point_buy() -> Self {
+        let average = (CHARACTERISTIC_POINTS / CHARACTERISTICS.len() as u32) as u8;
+        let mut investigator =
+            Self::with_characteristics("Francis Wayland Thurston", "Investigator", [average; 8]);
+        investigator.luck = 50;
+        investigator
+    }
+
+    fn with_characteristics(name: &str, occupation: &str, values: [u8; 8]) -> Self {
+        let characteristics = CHARACTERISTICS
+            .iter()
+            .zip(values)
+            .map(|(c, v)| (c.to_string(), v))
+            .collect();
+        let mut investigator = Self {
+            name: name.to_string(),
+            occupation: occupation.to_string(),
+            characteristics,
+            hp: 0,
+            max_hp: 0,
+            sanity: 0,
+            max_sanity: 0,
+            luck: 0,
+            skills: BTreeMap::new(),
+        };
+        for (skill, _) in BASE_SKILLS {
+            let base = investigator.base_skill(skill);
+            investigator.skills.insert(skill.to_string(), base);
+        }
+        investigator.derive_attributes();
+        investigator
+    }
+
+    pub fn characteristic(&self, name: &str) -> u8 {
+        self.characteristics.get(name).copied().unwrap_or(0)
+    }
+
+    pub fn skill(&self, name: &str) -> u8 {
+        self.skills
+            .get(name)
+            .copied()
+            .unwrap_or_else(|| self.base_skill(name))
+    }
+
+    pub(crate) fn base_skill(&self, name: &str) -> u8 {
+        if name == "Dodge" {
+            return self.characteristic("DEX") / 2;
+        }
+        BASE_SKILLS
+            .iter()
+            .find(|(skill, _)| *skill == name)
+            .map(|(_, base)| *base)
+            .unwrap_or(0)
+    }
+
+    // hit points and sanity follow from the characteristics
+    pub(crate) fn derive_attributes(&mut self) {
+        self.max_hp = (self.characteristic("CON") as i32 + self.characteristic("SIZ") as i32) / 10;
+        self.hp = self.max_hp;
+        self.sanity = self.characteristic("POW") as i32;
+        self.max_sanity = 99 - self.skill("Cthulhu Mythos") as i32;
+    }
+
+    pub(crate) fn characteristic_points_left(&self) -> i32 {
+        CHARACTERISTIC_POINTS as i32
+            - self
+                .characteristics
+                .values()
+                .map(|v| *v as i32)
+                .sum::<i32>()
+    }
+
+    // occupation and personal interest points: EDU x 4 + INT x 2
+    pub(crate) fn skill_points_left(&self) -> i32 {
+        let pool = self.characteristic("EDU") as i32 * 4 + self.characteristic("INT") as i32 * 2;
+        let spent = self
+            .skills
+            .iter()
+            .map(|(skill, value)| *value as i32 - self.base_skill(skill) as i32)
+            .sum::<i32>();
+        pool - spent
+    }
+
+    // point-buy step on a characteristic, refused when out of range or points
+    pub(crate) fn adjust_characteristic(&mut self, name: &str, delta: i32) -> bool {
+        let value = self.characteristic(name) as i32 + delta;
+        if value < CHARACTERISTIC_MIN as i32
+            || value > CHARACTERISTIC_MAX as i32
+            || (delta > 0 && self.characteristic_points_left() < delta)
+        {
+            return false;
+        }
+        let old_bases = BASE_SKILLS.map(|(skill, _)| self.base_skill(skill));
+        self.characteristics.insert(name.to_string(), value as u8);
+        // skills derived from the characteristic move with their base, the points
+        // spent on them stay spent
+        for ((skill, _), old_base) in BASE_SKILLS.iter().zip(old_bases) {
+            let base = self.base_skill(skill);
+            let value = self.skills.entry(skill.to_string()).or_insert(old_base);
+            *value = (*value as i32 - old_base as i32 + base as i32)
+                .clamp(base as i32, SKILL_MAX as i32) as u8;
+        }
+        self.derive_attributes();
+        true
+    }
+
+    // point-buy step on a skill, refused when out of range or points
+    pub(crate) fn adjust_skill(&mut self, name: &str, delta: i32) -> bool {
+        let value = self.skill(name) as i32 + delta;
+        if value < self.base_skill(name) as i32
+            || value > SKILL_MAX as i32
+            || (delta > 0 && self.skill_points_left() < delta)
+        {
+            return false;
+        }
+        self.skills.insert(name.to_string(), value as u8);
+        self.derive_attributes();
+        true
+    }
+
+    // short stat lines for the side panel
+    pub(crate) fn summary(&self) -> Vec<String> {
+        let mut lines = vec![
+            self.name.clone(),
+            format!(
+                "HP {}/{}  SAN {}/{}  Luck {}",
+                self.hp, self.max_hp, self.sanity, self.max_sanity, self.luck
+            ),
+        ];
+        lines.extend(CHARACTERISTICS.chunks(4).map(|chunk| {
+            chunk
+                .iter()
+                .map(|c| format!("{} {}", c, self.characteristic(c)))
+                .collect::<Vec<_>>()
+                .join("  ")
+        }));
+        let mut best = self
+            .skills
+            .iter()
+            .filter(|(skill, value)| **value > self.base_skill(skill))
+            .collect::<Vec<_>>();
+        best.sort_by(|a, b| b.1.cmp(a.1));
+        lines.extend(
+            best.iter()
+                .take(4)
+                .map(|(skill, value)| format!("{} {}%", skill, value)),
+        );
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ready_made_investigators_fit_the_point_buy() {
+        for investigator in [Investigator::thurston(), Investigator::point_buy()] {
+            assert!(investigator.characteristic_points_left() >= 0);
+            assert!(investigator.skill_points_left() >= 0);
+            assert!(
+                investigator
+                    .characteristics
+                    .values()
+                    .all(|v| (CHARACTERISTIC_MIN..=CHARACTERISTIC_MAX).contains(v))
+            );
+        }
+        assert_eq!(Investigator::thurston().characteristic_points_left(), 0);
+    }
+
+    #[test]
+    fn dodge_follows_dex_without_costing_skill_points() {
+        let mut investigator = Investigator::point_buy();
+        assert_eq!(investigator.skill("Dodge"), 28);
+        assert!(investigator.adjust_skill("Dodge", 10));
+        let points = investigator.skill_points_left();
+        assert!(investigator.adjust_characteristic("DEX", -10));
+        assert_eq!(investigator.base_skill("Dodge"), 23);
+        assert_eq!(investigator.skill("Dodge"), 33);
+        assert_eq!(investigator.skill_points_left(), points);
+        assert!(investigator.adjust_characteristic("DEX", 14));
+        assert_eq!(investigator.skill("Dodge"), 40);
+        assert_eq!(investigator.skill_points_left(), points);
+    }
+
+    #[test]
+    fn point_buy_stays_within_limits() {
+        let mut investigator = Investigator::point_buy();
+        // all points are spread already
+        assert!(!investigator.adjust_characteristic("STR", 5));
+        assert!(investigator.adjust_characteristic("STR", -5));
+        assert!(investigator.adjust_characteristic("POW", 5));
+        assert_eq!(investigator.sanity, 62);
+        assert!(!investigator.adjust_characteristic("APP", -50));
+        assert!(!investigator.adjust_skill("Climb", -1));
+        assert!(investigator.adjust_skill("Climb", 70));
+        assert!(!investigator.adjust_skill("Climb", 1));
+    }
+}
@@ -0,0 +1,298 @@
+use std::{
+    collections::{BTreeSet, HashMap, VecDeque},
+    fmt,
+};
+
+use crate::{engine::story::Story, screens::graph::GameGraph};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Severity {
+    Error,   // the story can't be played through, the game refuses to start
+    Warning, // suspicious, but playable
+}
+
+// single finding of the story linter
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Lint {
+    pub(crate) severity: Severity,
+    pub(crate) screen_no: usize,
+    pub(crate) message: String,
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(
+            f,
+            "{}: screen {}: {}",
+            severity, self.screen_no, self.message
+        )
+    }
+}
+
+// screens reachable from the given ones following the edges
+fn reachable(from: &[usize], edges: impl Fn(usize) -> Vec<usize>) -> BTreeSet<usize> {
+    let mut seen = from.iter().copied().collect::<BTreeSet<_>>();
+    let mut queue = from.iter().copied().collect::<VecDeque<_>>();
+    while let Some(screen_no) = queue.pop_front() {
+        for next in edges(screen_no) {
+            if seen.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+    seen
+}
+
+// checks the structure of the story graph, errors first
+pub(crate) fn lint(graph: &GameGraph, story: &Story) -> Vec<Lint> {
+    let mut lints = Vec::new();
+    let mut report = |severity, screen_no, message: String| {
+        lints.push(Lint {
+            severity,
+            screen_no,
+            message,
+        })
+    };
+
+    let mut screens = graph.graph.keys().copied().collect::<Vec<_>>();
+    screens.sort_unstable();
+
+    for &screen_no in &screens {
+        let targets = &graph.graph[&screen_no];
+        let choices = graph
+            .choices
+            .get(&screen_no)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        for target in targets.iter().collect::<BTreeSet<_>>() {
+            if !graph.graph.contains_key(target) {
+                report(
+                    Severity::Error,
+                    screen_no,
+                    format!("links to screen {} which does not exist", target),
+                );
+            }
+        }
+        if graph.endings.contains(&screen_no) {
+            if !choices.is_empty() {
+                report(
+                    Severity::Warning,
+                    screen_no,
+                    format!("is an ending but declares {} choice(s)", choices.len()),
+                );
+            }
+        } else if targets.is_empty() {
+            report(
+                Severity::Error,
+                screen_no,
+                "is not an ending and has no choices".to_string(),
+            );
+        } else if !choices.is_empty() && choices.iter().all(|c| c.condition.is_some()) {
+            // conditions are only known at runtime, they may well always hold
+            report(
+                Severity::Warning,
+                screen_no,
+                "every choice has a condition, it is a dead end when none of them holds"
+                    .to_string(),
+            );
+        }
+        for choice in choices {
+            if choice.text.trim().is_empty() {
+                report(
+                    Severity::Warning,
+                    screen_no,
+                    format!(
+                        "choice leading to screen {} has an empty label",
+                        choice.next
+                    ),
+                );
+            }
+        }
+    }
+
+    // the story stops on an ending, whatever it declares
+    let from_start = reachable(&[story.start], |screen_no| {
+        if graph.endings.contains(&screen_no) {
+            Vec::new()
+        } else {
+            graph.graph.get(&screen_no).cloned().unwrap_or_default()
+        }
+    });
+    for &screen_no in &screens {
+        if !from_start.contains(&screen_no) {
+            report(
+                Severity::Warning,
+                screen_no,
+                format!("is unreachable from the start screen {}", story.start),
+            );
+        }
+    }
+
+    // walk the edges backwards from the endings to find screens that can still end
+    let mut reversed: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (&screen_no, targets) in &graph.graph {
+        if graph.endings.contains(&screen_no) {
+            continue;
+        }
+        for &target in targets {
+            reversed.entry(target).or_default().push(screen_no);
+        }
+    }
+    let endings = graph.endings.iter().copied().collect::<Vec<_>>();
+    let can_end = reachable(&endings, |screen_no| {
+        reversed.get(&screen_no).cloned().unwrap_or_default()
+    });
+    for &screen_no in &screens {
+        if from_start.contains(&screen_no)
+            && !can_end.contains(&screen_no)
+            && !graph.graph[&screen_no].is_empty()
+        {
+            report(
+                Severity::Error,
+                screen_no,
+                "is caught in a cycle with no way to an ending".to_string(),
+            );
+        }
+    }
+
+    lints.sort_by_key(|l| (l.severity, l.screen_no));
+    lints
+}
+
+// prints the findings, fails when there is an error among them
+pub(crate) fn check(graph: &GameGraph, story: &Story) -> Result<(), Box<dyn std::error::Error>> {
+    let lints = lint(graph, story);
+    for l in &lints {
+        eprintln!("{}", l);
+    }
+    let errors = lints
+        .iter()
+        .filter(|l| l.severity == Severity::Error)
+        .count();
+    if errors > 0 {
+        return Err(format!("Story graph has {} error(s)", errors).into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::engine::vars::Condition;
+
+    fn story() -> Story {
+        Story {
+            title: String::new(),
+            start: 0,
+            exclude: BTreeSet::new(),
+            screens: Vec::new(),
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    // screens with the targets of their choices, the last screen is the ending
+    fn graph(screens: &[(usize, &[usize])]) -> GameGraph {
+        let mut graph = GameGraph::from_targets(screens);
+        if let Some((ending, _)) = screens.last() {
+            graph.endings.insert(*ending);
+        }
+        graph
+    }
+
+    fn found(graph: &GameGraph) -> Vec<(Severity, usize, String)> {
+        lint(graph, &story())
+            .into_iter()
+            .map(|l| (l.severity, l.screen_no, l.message))
+            .collect()
+    }
+
+    #[test]
+    fn a_sound_story_has_no_findings() {
+        assert!(found(&graph(&[(0, &[1, 2]), (1, &[2, 0]), (2, &[])])).is_empty());
+    }
+
+    #[test]
+    fn broken_links_and_dead_ends_are_errors() {
+        let graph = graph(&[(0, &[1, 3, 9]), (1, &[]), (2, &[3]), (3, &[])]);
+        assert_eq!(
+            found(&graph),
+            vec![
+                (
+                    Severity::Error,
+                    0,
+                    "links to screen 9 which does not exist".to_string()
+                ),
+                (
+                    Severity::Error,
+                    1,
+                    "is not an ending and has no choices".to_string()
+                ),
+                (
+                    Severity::Warning,
+                    2,
+                    "is unreachable from the start screen 0".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn cycles_without_an_ending_are_errors() {
+        let graph = graph(&[(0, &[1, 3]), (1, &[2]), (2, &[1]), (3, &[])]);
+        let errors = found(&graph)
+            .into_iter()
+            .map(|(severity, screen_no, _)| (severity, screen_no))
+            .collect::<Vec<_>>();
+        assert_eq!(errors, vec![(Severity::Error, 1), (Severity::Error, 2)]);
+    }
+
+    #[test]
+    fn suspicious_screens_are_warnings() {
+        let mut graph = graph(&[(0, &[1, 2]), (1, &[2]), (2, &[0])]);
+        graph.choices.get_mut(&0).unwrap()[0].text = "  ".to_string();
+        for choice in graph.choices.get_mut(&1).unwrap() {
+            choice.condition = Some(Condition::Has("key".to_string()));
+        }
+        let warnings = found(&graph)
+            .into_iter()
+            .map(|(severity, screen_no, message)| {
+                assert_eq!(severity, Severity::Warning);
+                (screen_no, message)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            warnings,
+            vec![
+                (
+                    0,
+                    "choice leading to screen 1 has an empty label".to_string()
+                ),
+                (
+                    1,
+                    "every choice has a condition, it is a dead end when none of them holds"
+                        .to_string()
+                ),
+                (2, "is an ending but declares 1 choice(s)".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn only_errors_stop_the_game() {
+        // screen 2 is unreachable, a warning
+        let warnings_only = graph(&[(0, &[1]), (2, &[1]), (1, &[])]);
+        assert!(!lint(&warnings_only, &story()).is_empty());
+        assert!(check(&warnings_only, &story()).is_ok());
+        let mut with_error = graph(&[(0, &[1]), (1, &[])]);
+        with_error.graph.insert(0, vec![1, 5]);
+        assert!(check(&with_error, &story()).is_err());
+        let sound = graph(&[(0, &[1]), (1, &[])]);
+        assert!(check(&sound, &story()).is_ok());
+    }
+}
@@ -0,0 +1,166 @@
+use std::ops::Range;
+
+use ratatui::style::{Color, Modifier, Style};
+
+// Lightweight markup of the screen texts, applied after the text is reflowed:
+//
+//   # Heading          a whole paragraph
+//   > quoted letter    a whole paragraph
+//   **bold**, *italic*, ~handwritten~ inside a paragraph
+//
+// a backslash escapes the next character.
+
+// plain text with style ranges (byte offsets, sorted and not overlapping)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StyledText {
+    pub text: String,
+    pub styles: Vec<(Range<usize>, Style)>,
+}
+
+impl StyledText {
+    pub fn push(&mut self, text: &str, style: Style) {
+        let start = self.text.len();
+        self.text.push_str(text);
+        if style == Style::default() {
+            return;
+        }
+        match self.styles.last_mut() {
+            Some((range, last)) if range.end == start && *last == style => {
+                range.end = self.text.len()
+            }
+            _ => self.styles.push((start..self.text.len(), style)),
+        }
+    }
+
+    // the same styles over another text, e.g. the part typed out so far
+    pub fn with_text(&self, text: String) -> Self {
+        Self {
+            text,
+            styles: self.styles.clone(),
+        }
+    }
+}
+
+impl From<String> for StyledText {
+    fn from(text: String) -> Self {
+        Self {
+            text,
+            styles: Vec::new(),
+        }
+    }
+}
+
+fn heading() -> Style {
+    Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+}
+
+fn quote() -> Style {
+    Style::default()
+        .fg(Color::Gray)
+        .add_modifier(Modifier::ITALIC)
+}
+
+fn handwritten() -> Style {
+    Style::default()
+        .fg(Color::LightMagenta)
+        .add_modifier(Modifier::ITALIC)
+}
+
+// parses reflowed text, paragraphs are separated by blank lines
+pub(crate) fn parse(source: &str) -> StyledText {
+    let mut out = StyledText::default();
+    for (i, paragraph) in source.split("\n\n").enumerate() {
+        if i > 0 {
+            out.push("\n\n", Style::default());
+        }
+        if let Some(title) = paragraph.strip_prefix("# ") {
+            inline(&mut out, title, heading());
+        } else if let Some(quoted) = paragraph.strip_prefix("> ") {
+            out.push("  ", Style::default());
+            inline(&mut out, quoted, quote());
+        } else {
+            inline(&mut out, paragraph, Style::default());
+        }
+    }
+    out
+}
+
+// emphasis inside a paragraph, an unclosed span runs to the end of the paragraph
+fn inline(out: &mut StyledText, paragraph: &str, base: Style) {
+    let (mut bold, mut italic, mut hand) = (false, false, false);
+    let mut chars = paragraph.chars().peekable();
+    let mut buf = [0; 4];
+    while let Some(c) = chars.next() {
+        let c = match c {
+            '\\' => match chars.next() {
+                Some(escaped) => escaped,
+                None => break,
+            },
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                bold = !bold;
+                continue;
+            }
+            '*' => {
+                italic = !italic;
+                continue;
+            }
+            '~' => {
+                hand = !hand;
+                continue;
+            }
+            c => c,
+        };
+        let mut style = if hand {
+            base.patch(handwritten())
+        } else {
+            base
+        };
+        if bold {
+            style = style.add_modifier(Modifier::BOLD);
+        }
+        if italic {
+            style = style.add_modifier(Modifier::ITALIC);
+        }
+        out.push(c.encode_utf8(&mut buf), style);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans(text: &StyledText) -> Vec<&str> {
+        text.styles
+            .iter()
+            .map(|(range, _)| &text.text[range.clone()])
+            .collect()
+    }
+
+    #[test]
+    fn markers_are_stripped_from_the_text() {
+        let text = parse("# Uvod\n\nnadpis: **„KULT CTHULHU“** a *Snová krajina*, 5 \\* 3");
+        assert_eq!(
+            text.text,
+            "Uvod\n\nnadpis: „KULT CTHULHU“ a Snová krajina, 5 * 3"
+        );
+        assert_eq!(
+            spans(&text),
+            vec!["Uvod", "„KULT CTHULHU“", "Snová krajina"]
+        );
+        assert_eq!(text.styles[0].1, heading());
+        assert!(text.styles[1].1.add_modifier.contains(Modifier::BOLD));
+        assert!(text.styles[2].1.add_modifier.contains(Modifier::ITALIC));
+    }
+
+    #[test]
+    fn quotes_and_handwriting_keep_their_style() {
+        let text = parse("> Milý synovče, ~G. A.~\n\nkonec");
+        assert_eq!(text.text, "  Milý synovče, G. A.\n\nkonec");
+        assert_eq!(spans(&text), vec!["Milý synovče, ", "G. A."]);
+        assert_eq!(text.styles[0].1, quote());
+        assert_eq!(text.styles[1].1.fg, Some(Color::LightMagenta));
+    }
+}
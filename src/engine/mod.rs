@@ -1,4 +1,20 @@
+pub(crate) mod achievements;
 pub(crate) mod config;
+pub(crate) mod dice;
+pub(crate) mod export;
 pub(crate) mod fs;
 pub(crate) mod gfx;
+pub(crate) mod graphics;
+pub(crate) mod image_cache;
+pub(crate) mod inventory;
+pub(crate) mod investigator;
+pub(crate) mod lint;
+pub(crate) mod markup;
+pub(crate) mod palette;
+pub(crate) mod progress;
+pub(crate) mod sanity;
+pub(crate) mod save;
+pub(crate) mod story;
+pub(crate) mod timeline;
 pub(crate) mod validate;
+pub(crate) mod vars;
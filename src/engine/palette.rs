@@ -0,0 +1,254 @@
+use ratatui::style::Color;
+use serde::Deserialize;
+
+use crate::engine::{config, fs::Bitmap};
+
+// how many colours the terminal can show
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum ColorDepth {
+    #[serde(rename = "truecolor")]
+    TrueColor,
+    #[serde(rename = "256")]
+    Ansi256,
+    #[serde(rename = "16")]
+    Ansi16, // the Linux console, screen, old terminals over SSH
+}
+
+impl ColorDepth {
+    // terminals announcing 24-bit colour set COLORTERM, the rest only tell their TERM
+    pub(crate) fn detect() -> Self {
+        let var = |name| std::env::var(name).unwrap_or_default();
+        let (colorterm, term) = (var("COLORTERM"), var("TERM"));
+        if colorterm == "truecolor"
+            || colorterm == "24bit"
+            || term.contains("truecolor")
+            || term.contains("direct")
+        {
+            ColorDepth::TrueColor
+        } else if term.contains("256color") {
+            ColorDepth::Ansi256
+        } else {
+            ColorDepth::Ansi16
+        }
+    }
+}
+
+// how the error of a reduced colour is spread over the neighbouring pixels
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Dither {
+    #[default]
+    None,
+    Ordered,        // 4x4 Bayer matrix, stable while the image animates
+    FloydSteinberg, // error diffusion, smoother but noisy when the image moves
+}
+
+// xterm's default values of the 16 ANSI colours
+const ANSI16: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+// named colours are sent as the basic SGR codes every terminal knows
+const NAMED: [Color; 16] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Gray,
+    Color::DarkGray,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::LightYellow,
+    Color::LightBlue,
+    Color::LightMagenta,
+    Color::LightCyan,
+    Color::White,
+];
+
+// channel levels of the 6x6x6 colour cube at indices 16..=231
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+// nearest entry of the xterm-256 palette, the first 16 are left out as terminals
+// let the user redefine them
+fn nearest_256(rgb: (u8, u8, u8)) -> (u8, (u8, u8, u8)) {
+    let level = |c: u8| match c {
+        0..48 => 0,
+        48..115 => 1,
+        _ => (c as usize - 35) / 40,
+    };
+    let (r, g, b) = (level(rgb.0), level(rgb.1), level(rgb.2));
+    let cube = (CUBE[r], CUBE[g], CUBE[b]);
+    let average = (rgb.0 as usize + rgb.1 as usize + rgb.2 as usize) / 3;
+    let step = (average.saturating_sub(3) / 10).min(23);
+    let grey = (8 + step * 10) as u8;
+    if distance(rgb, (grey, grey, grey)) < distance(rgb, cube) {
+        (232 + step as u8, (grey, grey, grey))
+    } else {
+        ((16 + r * 36 + g * 6 + b) as u8, cube)
+    }
+}
+
+fn nearest_16(rgb: (u8, u8, u8)) -> (u8, (u8, u8, u8)) {
+    let index = (0..16)
+        .min_by_key(|&i| distance(rgb, ANSI16[i]))
+        .unwrap_or_default();
+    (index as u8, ANSI16[index])
+}
+
+// the colours the images are drawn with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Colors {
+    pub depth: ColorDepth,
+    pub dither: Dither,
+}
+
+impl Colors {
+    pub(crate) fn new(config: &config::Config) -> Self {
+        Self {
+            depth: config.colors.unwrap_or_else(ColorDepth::detect),
+            dither: config.dither.unwrap_or_default(),
+        }
+    }
+
+    fn nearest(&self, rgb: (u8, u8, u8)) -> (u8, (u8, u8, u8)) {
+        match self.depth {
+            ColorDepth::TrueColor => (0, rgb),
+            ColorDepth::Ansi256 => nearest_256(rgb),
+            ColorDepth::Ansi16 => nearest_16(rgb),
+        }
+    }
+
+    // terminal colour of a pixel
+    pub(crate) fn color(&self, rgb: (u8, u8, u8)) -> Color {
+        let (r, g, b) = rgb;
+        match self.depth {
+            ColorDepth::TrueColor => Color::Rgb(r, g, b),
+            ColorDepth::Ansi256 => Color::Indexed(nearest_256(rgb).0),
+            ColorDepth::Ansi16 => NAMED[nearest_16(rgb).0 as usize],
+        }
+    }
+
+    // reduces the image to the colours of the palette
+    pub(crate) fn quantize(&self, image: &mut Bitmap) {
+        // roughly the distance between neighbouring palette colours
+        let spread = match self.depth {
+            ColorDepth::TrueColor => return,
+            ColorDepth::Ansi256 => 40.0,
+            ColorDepth::Ansi16 => 128.0,
+        };
+        match self.dither {
+            Dither::None => {
+                for pixel in image.iter_mut().flatten() {
+                    *pixel = self.nearest(*pixel).1;
+                }
+            }
+            Dither::Ordered => {
+                for (y, row) in image.iter_mut().enumerate() {
+                    for (x, pixel) in row.iter_mut().enumerate() {
+                        let threshold = (BAYER[y % 4][x % 4] as f32 + 0.5) / 16.0 - 0.5;
+                        let shift = |c: u8| (c as f32 + threshold * spread).clamp(0.0, 255.0) as u8;
+                        *pixel = self
+                            .nearest((shift(pixel.0), shift(pixel.1), shift(pixel.2)))
+                            .1;
+                    }
+                }
+            }
+            Dither::FloydSteinberg => self.diffuse(image),
+        }
+    }
+
+    // Floyd-Steinberg: 7/16 of the error to the right, 3/16, 5/16 and 1/16 to the row below
+    fn diffuse(&self, image: &mut Bitmap) {
+        let width = image.first().map_or(0, Vec::len);
+        let mut errors = vec![[0.0f32; 3]; width + 2];
+        for row in image.iter_mut() {
+            let mut below = vec![[0.0f32; 3]; width + 2];
+            for x in 0..row.len().min(width) {
+                let (r, g, b) = row[x];
+                let wanted = [r, g, b]
+                    .iter()
+                    .zip(errors[x + 1])
+                    .map(|(&c, e)| (c as f32 + e).clamp(0.0, 255.0))
+                    .collect::<Vec<_>>();
+                let (_, got) = self.nearest((wanted[0] as u8, wanted[1] as u8, wanted[2] as u8));
+                row[x] = got;
+                for (channel, &c) in [got.0, got.1, got.2].iter().enumerate() {
+                    let error = wanted[channel] - c as f32;
+                    errors[x + 2][channel] += error * 7.0 / 16.0;
+                    below[x][channel] += error * 3.0 / 16.0;
+                    below[x + 1][channel] += error * 5.0 / 16.0;
+                    below[x + 2][channel] += error / 16.0;
+                }
+            }
+            errors = below;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn colors(depth: ColorDepth, dither: Dither) -> Colors {
+        Colors { depth, dither }
+    }
+
+    #[test]
+    fn colours_snap_to_the_xterm_palettes() {
+        assert_eq!(nearest_256((255, 0, 0)), (196, (255, 0, 0)));
+        assert_eq!(nearest_256((100, 100, 100)), (241, (98, 98, 98)));
+        assert_eq!(nearest_16((250, 10, 10)), (9, (255, 0, 0)));
+        let ansi16 = colors(ColorDepth::Ansi16, Dither::None);
+        assert_eq!(ansi16.color((0, 0, 0)), Color::Black);
+        let ansi256 = colors(ColorDepth::Ansi256, Dither::None);
+        assert_eq!(ansi256.color((0, 0, 255)), Color::Indexed(21));
+    }
+
+    #[test]
+    fn dithering_mixes_palette_colours_into_the_average() {
+        // mid grey is not in the 16 colour palette, it is mixed from black and white
+        for dither in [Dither::Ordered, Dither::FloydSteinberg] {
+            let mut image = vec![vec![(160, 160, 160); 16]; 16];
+            colors(ColorDepth::Ansi16, dither).quantize(&mut image);
+            assert!(image.iter().flatten().all(|p| ANSI16.contains(p)));
+            let mut shades = image.iter().flatten().collect::<Vec<_>>();
+            shades.sort_unstable();
+            shades.dedup();
+            assert!(shades.len() > 1, "{:?} gives a flat image", dither);
+            let mean = image.iter().flatten().map(|p| p.0 as f32).sum::<f32>() / 256.0;
+            assert!((mean - 160.0).abs() < 20.0, "{:?} mean {}", dither, mean);
+        }
+    }
+
+    #[test]
+    fn true_colour_is_left_alone() {
+        let mut image = vec![vec![(1, 2, 3)]];
+        colors(ColorDepth::TrueColor, Dither::FloydSteinberg).quantize(&mut image);
+        assert_eq!(image, vec![vec![(1, 2, 3)]]);
+    }
+}
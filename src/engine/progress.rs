@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+
+use crate::engine::{
+    inventory::Inventory,
+    investigator::Investigator,
+    sanity::Insanity,
+    vars::{Condition, Effect, Vars},
+};
+
+// the player's state carried from screen to screen and stored in the save slots
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Progress {
+    #[serde(default)]
+    pub(crate) vars: Vars,
+    #[serde(default)]
+    pub(crate) investigator: Investigator,
+    #[serde(default)]
+    pub(crate) inventory: Inventory,
+    #[serde(skip)]
+    pub(crate) session_sanity_lost: i32, // since the game was started or loaded
+    #[serde(default)]
+    pub(crate) insanity: Insanity,
+    #[serde(default)]
+    pub(crate) redirect: Option<usize>, // the only way on from the current screen
+    #[serde(skip)]
+    pub(crate) notice: Option<String>, // shown under the text of the current screen
+}
+
+impl Progress {
+    pub fn new(investigator: Investigator) -> Self {
+        Self {
+            vars: Vars::default(),
+            investigator,
+            inventory: Inventory::default(),
+            session_sanity_lost: 0,
+            insanity: Insanity::Sane,
+            redirect: None,
+            notice: None,
+        }
+    }
+
+    pub(crate) fn check(&self, condition: &Condition) -> bool {
+        match condition {
+            Condition::IsSet(name) => self.vars.get(name) != 0,
+            Condition::NotSet(name) => self.vars.get(name) == 0,
+            Condition::Eq(name, value) => self.vars.get(name) == *value,
+            Condition::Gte(name, value) => self.vars.get(name) >= *value,
+            Condition::Lte(name, value) => self.vars.get(name) <= *value,
+            Condition::Has(item) => self.inventory.has(item),
+            Condition::Lacks(item) => !self.inventory.has(item),
+            Condition::Not(condition) => !self.check(condition),
+            Condition::All(conditions) => conditions.iter().all(|c| self.check(c)),
+            Condition::Any(conditions) => conditions.iter().any(|c| self.check(c)),
+        }
+    }
+
+    pub(crate) fn apply(&mut self, effects: &[Effect]) {
+        for effect in effects {
+            match effect {
+                Effect::Set(name) => self.vars.set(name, 1),
+                Effect::Add(name, value) => self.vars.add(name, *value),
+                Effect::Clear(name) => self.vars.clear(name),
+                Effect::Give(item) => self.inventory.give(item),
+                Effect::Take(item) => self.inventory.take(item),
+            }
+        }
+    }
+
+    // how far the investigator's mind has slipped, 0.0 (sane) to 1.0
+    pub fn madness(&self) -> f32 {
+        let pow = self.investigator.characteristic("POW").max(1) as f32;
+        let lost = (1.0 - self.investigator.sanity as f32 / pow).clamp(0.0, 1.0) * 0.6;
+        let insanity = match self.insanity {
+            Insanity::Sane => 0.0,
+            Insanity::Temporary(_) => 0.3,
+            Insanity::Indefinite => 0.5,
+        };
+        (lost + insanity).min(1.0)
+    }
+}
@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+
+use crate::engine::{dice::Dice, progress::Progress};
+
+// Sanity loss of a horrifying screen, e.g. `{"loss": "1/1d6", "temporary": 77}`,
+// the redirect screens are optional, without them insanity only shows on screen
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct SanityCheck {
+    pub(crate) loss: String, // `<loss on success>/<loss on failure>`
+    pub(crate) temporary: Option<usize>,
+    pub(crate) indefinite: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub enum Insanity {
+    #[default]
+    Sane,
+    Temporary(u32), // number of screens until it wears off
+    Indefinite,
+}
+
+// losing this much in a single roll brings on a temporary insanity
+const TEMPORARY_THRESHOLD: i32 = 5;
+
+impl Insanity {
+    // temporary insanity wears off as the player moves on
+    pub(crate) fn next_screen(&mut self) {
+        if let Insanity::Temporary(screens) = self {
+            *screens = screens.saturating_sub(1);
+            if *screens == 0 {
+                *self = Insanity::Sane;
+            }
+        }
+    }
+}
+
+// rolls the sanity check and applies the loss, returns the screen
+// the player is sent to when the loss drives them insane
+pub(crate) fn apply(
+    check: &SanityCheck,
+    progress: &mut Progress,
+    dice: &mut Dice,
+) -> Option<usize> {
+    let investigator = &mut progress.investigator;
+    let (on_success, on_failure) = check.loss.split_once('/').unwrap_or(("0", &check.loss));
+    let roll = dice.d100();
+    let success = (roll as i32) <= investigator.sanity;
+    let expr = if success { on_success } else { on_failure };
+    let loss = dice.roll_expr(expr).unwrap_or(0) as i32;
+    let before = investigator.sanity;
+    investigator.sanity = (investigator.sanity - loss).max(0);
+    progress.session_sanity_lost += loss;
+
+    progress.notice = Some(format!(
+        "Sanity roll {:02} vs {}: {}, lost {} SAN",
+        roll,
+        before,
+        if success { "success" } else { "failure" },
+        loss
+    ));
+
+    // a fifth of the sanity the session started with lost in this session (the rules'
+    // "one day" of play), or all of it
+    let session_start = investigator.sanity + progress.session_sanity_lost;
+    let indefinite = investigator.sanity == 0 || progress.session_sanity_lost >= session_start / 5;
+    if indefinite && progress.insanity != Insanity::Indefinite && loss > 0 {
+        progress.insanity = Insanity::Indefinite;
+        return check.indefinite.or(check.temporary);
+    }
+    if loss >= TEMPORARY_THRESHOLD && progress.insanity == Insanity::Sane {
+        progress.insanity = Insanity::Temporary(dice.roll(10));
+        return check.temporary;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(loss: &str) -> SanityCheck {
+        SanityCheck {
+            loss: loss.to_string(),
+            temporary: Some(77),
+            indefinite: Some(78),
+        }
+    }
+
+    fn progress(sanity: i32) -> Progress {
+        let mut progress = Progress::default();
+        progress.investigator.sanity = sanity;
+        progress
+    }
+
+    #[test]
+    fn small_losses_only_lower_sanity() {
+        let mut progress = progress(60);
+        let mut dice = Dice::new(Some(1));
+        assert_eq!(apply(&check("2/2"), &mut progress, &mut dice), None);
+        assert_eq!(progress.investigator.sanity, 58);
+        assert_eq!(progress.insanity, Insanity::Sane);
+        assert!(progress.notice.as_ref().unwrap().ends_with("lost 2 SAN"));
+    }
+
+    #[test]
+    fn a_big_loss_brings_on_temporary_insanity() {
+        let mut progress = progress(60);
+        let mut dice = Dice::new(Some(1));
+        assert_eq!(apply(&check("5/5"), &mut progress, &mut dice), Some(77));
+        let Insanity::Temporary(screens) = progress.insanity else {
+            panic!("{:?}", progress.insanity);
+        };
+        assert!((1..=10).contains(&screens));
+        for _ in 0..screens {
+            progress.insanity.next_screen();
+        }
+        assert_eq!(progress.insanity, Insanity::Sane);
+    }
+
+    #[test]
+    fn a_fifth_lost_in_one_session_is_indefinite() {
+        let mut progress = progress(60);
+        let mut dice = Dice::new(Some(1));
+        for _ in 0..3 {
+            assert_eq!(apply(&check("3/3"), &mut progress, &mut dice), None);
+        }
+        assert_eq!(apply(&check("3/3"), &mut progress, &mut dice), Some(78));
+        assert_eq!(progress.insanity, Insanity::Indefinite);
+    }
+
+    #[test]
+    fn losses_of_earlier_sessions_do_not_count() {
+        let mut progress = progress(60);
+        let mut dice = Dice::new(Some(1));
+        for _ in 0..3 {
+            apply(&check("3/3"), &mut progress, &mut dice);
+        }
+        // saved and loaded again, a new session
+        let json = serde_json::to_string(&progress).unwrap();
+        let mut progress: Progress = serde_json::from_str(&json).unwrap();
+        assert_eq!(progress.session_sanity_lost, 0);
+        assert_eq!(apply(&check("1/1"), &mut progress, &mut dice), None);
+        assert_eq!(progress.insanity, Insanity::Sane);
+    }
+
+    #[test]
+    fn losing_all_sanity_is_indefinite() {
+        let mut progress = progress(3);
+        let mut dice = Dice::new(Some(1));
+        let mut check = check("3/3");
+        check.indefinite = None;
+        // without an indefinite screen the temporary one is used
+        assert_eq!(apply(&check, &mut progress, &mut dice), Some(77));
+        assert_eq!(progress.investigator.sanity, 0);
+        assert_eq!(progress.insanity, Insanity::Indefinite);
+    }
+}
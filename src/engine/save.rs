@@ -0,0 +1,243 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::{config, progress::Progress, timeline::Timeline};
+
+// single save slot, stored as one json file in the save directory
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct SaveGame {
+    pub(crate) name: String,
+    pub(crate) saved_at: u64, // seconds since the unix epoch
+    pub(crate) screen_no: usize,
+    pub(crate) visited: HashMap<usize, usize>,
+    pub(crate) path: Vec<usize>,
+    #[serde(default)]
+    pub(crate) timeline: Timeline,
+    #[serde(flatten)]
+    pub(crate) progress: Progress,
+}
+
+impl SaveGame {
+    pub(crate) fn new(
+        name: &str,
+        screen_no: usize,
+        visited: &HashMap<usize, usize>,
+        path: &[usize],
+        timeline: &Timeline,
+        progress: &Progress,
+    ) -> Self {
+        let mut timeline = timeline.clone();
+        timeline.pause();
+        Self {
+            name: name.to_string(),
+            saved_at: now(),
+            screen_no,
+            visited: visited.clone(),
+            path: path.to_vec(),
+            timeline,
+            progress: progress.clone(),
+        }
+    }
+
+    // one line summary shown in the slot picker
+    pub(crate) fn describe(&self) -> String {
+        format!(
+            "{} - screen {} - {} screens visited - {}",
+            self.name,
+            self.screen_no,
+            self.visited.len(),
+            format_timestamp(self.saved_at)
+        )
+    }
+}
+
+pub(crate) fn save_dir(config: &config::Config) -> PathBuf {
+    PathBuf::from(config.save_path.as_deref().unwrap_or("saves/"))
+}
+
+// slot names are used as file names, anything but letters, digits, '-' and '_' is
+// percent-encoded so that different names never share a file
+fn slot_file(config: &config::Config, name: &str) -> PathBuf {
+    let mut file_name = String::new();
+    for c in name.chars() {
+        if c.is_alphanumeric() || c == '-' || c == '_' {
+            file_name.push(c);
+        } else {
+            let mut buf = [0; 4];
+            for byte in c.encode_utf8(&mut buf).bytes() {
+                file_name.push_str(&format!("%{:02X}", byte));
+            }
+        }
+    }
+    save_dir(config).join(format!("{}.json", file_name))
+}
+
+pub(crate) fn write(
+    config: &config::Config,
+    save: &SaveGame,
+) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::create_dir_all(save_dir(config))?;
+    let contents = serde_json::to_string_pretty(save)?;
+    let mut file = File::create(slot_file(config, &save.name))?;
+    file.write_all(contents.as_bytes())?;
+    Ok(())
+}
+
+fn read(path: &Path) -> Result<SaveGame, Box<dyn std::error::Error>> {
+    let mut file = File::open(path)?;
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)?;
+    Ok(serde_json::from_str(&contents)?)
+}
+
+pub(crate) fn read_slot(
+    config: &config::Config,
+    name: &str,
+) -> Result<SaveGame, Box<dyn std::error::Error>> {
+    read(&slot_file(config, name))
+}
+
+// all readable slots, most recent first
+pub(crate) fn list_slots(config: &config::Config) -> Vec<SaveGame> {
+    let pattern = format!("{}/*.json", save_dir(config).to_string_lossy());
+    let mut slots = match glob::glob(&pattern) {
+        Ok(paths) => paths
+            .flatten()
+            .filter_map(|path| read(&path).ok())
+            .collect::<Vec<_>>(),
+        Err(_) => Vec::new(),
+    };
+    slots.sort_by_key(|s| std::cmp::Reverse(s.saved_at));
+    slots
+}
+
+pub(crate) fn latest(config: &config::Config) -> Option<SaveGame> {
+    list_slots(config).into_iter().next()
+}
+
+// writes the journey into the save directory for external tools, returns the file
+pub(crate) fn export_timeline(
+    config: &config::Config,
+    timeline: &Timeline,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let dir = save_dir(config).join("journeys");
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("journey-{}.json", now()));
+    let mut file = File::create(&path)?;
+    file.write_all(timeline.to_json()?.as_bytes())?;
+    Ok(path)
+}
+
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// formats unix time as `YYYY-MM-DD HH:MM` (UTC)
+pub(crate) fn format_timestamp(secs: u64) -> String {
+    let days = (secs / 86400) as i64;
+    let rem = secs % 86400;
+    // civil from days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        rem / 3600,
+        (rem % 3600) / 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(dir: &str) -> config::Config {
+        config::Config {
+            save_path: Some(
+                std::env::temp_dir()
+                    .join(format!("coc-save-{}-{}", dir, std::process::id()))
+                    .to_string_lossy()
+                    .into_owned(),
+            ),
+            ..config::Config::default()
+        }
+    }
+
+    #[test]
+    fn different_names_get_different_files() {
+        let cfg = config("names");
+        let names = ["a b", "a_b", "a%20b", "a/b", "a.b", "ab", "Příbram"];
+        let mut files = names
+            .iter()
+            .map(|name| slot_file(&cfg, name))
+            .collect::<Vec<_>>();
+        assert_eq!(files[1], save_dir(&cfg).join("a_b.json"));
+        assert_eq!(files[0], save_dir(&cfg).join("a%20b.json"));
+        assert!(
+            files
+                .iter()
+                .all(|f| f.parent() == Some(save_dir(&cfg).as_path()))
+        );
+        files.sort();
+        files.dedup();
+        assert_eq!(files.len(), names.len());
+    }
+
+    #[test]
+    fn slots_survive_a_write_and_read() {
+        let cfg = config("roundtrip");
+        let mut progress = Progress::default();
+        progress.vars.set("met_the_professor", 1);
+        progress.inventory.give("revolver");
+        let mut timeline = Timeline::default();
+        timeline.record(0, None);
+        timeline.record(17, Some("Open the door"));
+        let visited = HashMap::from([(0, 1), (17, 2)]);
+        let save = SaveGame::new(
+            "Before the cellar",
+            17,
+            &visited,
+            &[0, 17],
+            &timeline,
+            &progress,
+        );
+        assert!(
+            save.timeline
+                .pauses
+                .last()
+                .is_some_and(|p| p.until.is_none()),
+            "the clock stops while the game is saved"
+        );
+        write(&cfg, &save).unwrap();
+        assert_eq!(read_slot(&cfg, "Before the cellar").unwrap(), save);
+        assert_eq!(latest(&cfg), Some(save));
+        std::fs::remove_dir_all(save_dir(&cfg)).unwrap();
+    }
+
+    #[test]
+    fn timestamps_are_formatted_as_utc_dates() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00");
+        // leap day and the last minute of a year
+        assert_eq!(format_timestamp(951_827_696), "2000-02-29 12:34");
+        assert_eq!(format_timestamp(1_704_067_199), "2023-12-31 23:59");
+    }
+}
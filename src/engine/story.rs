@@ -0,0 +1,75 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Deserialize;
+
+use crate::engine::{config, fs};
+
+// story manifest, story.json in the data path, e.g.
+// `{"title": "Volání Cthulhu", "start": 0, "exclude": [112], "metadata": {"author": "..."}}`,
+// the screens themselves are discovered from the data files
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct Story {
+    pub(crate) title: String,
+    #[serde(default)]
+    pub(crate) start: usize,
+    // screens with data files that are not part of the story, e.g. unfinished chapters
+    #[serde(default)]
+    pub(crate) exclude: BTreeSet<usize>,
+    #[serde(skip)]
+    pub(crate) screens: Vec<usize>,
+    #[serde(default)]
+    pub(crate) metadata: BTreeMap<String, String>,
+}
+
+impl Story {
+    pub(crate) fn load(config: &config::Config) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut story = fs::read_story(config)?.unwrap_or_else(|| Self {
+            title: "Call of Cthulhu".to_string(),
+            start: 0,
+            exclude: BTreeSet::new(),
+            screens: Vec::new(),
+            metadata: BTreeMap::new(),
+        });
+        story.set_screens(fs::discover_screens(config)?);
+        Ok(story)
+    }
+
+    // the screens found in the data files, minus the excluded ones
+    fn set_screens(&mut self, found: BTreeSet<usize>) {
+        self.screens = found
+            .into_iter()
+            .filter(|screen_no| !self.exclude.contains(screen_no))
+            .collect();
+    }
+
+    pub(crate) fn contains(&self, screen_no: usize) -> bool {
+        self.screens.binary_search(&screen_no).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn screens_are_discovered_from_the_data_files() {
+        let config = config::Config {
+            data_path: "assets/".to_string(),
+            ..config::Config::default()
+        };
+        let story = Story::load(&config).unwrap();
+        assert_eq!(story.title, "Volání Cthulhu");
+        assert_eq!(story.screens.len(), 101);
+        assert!(story.contains(story.start));
+        assert!(story.contains(111));
+        assert!(!story.contains(5));
+    }
+
+    #[test]
+    fn excluded_screens_are_left_out() {
+        let mut story: Story = serde_json::from_str(r#"{"title": "", "exclude": [2]}"#).unwrap();
+        story.set_screens(BTreeSet::from([0, 1, 2, 3]));
+        assert_eq!(story.screens, vec![0, 1, 3]);
+        assert!(!story.contains(2));
+    }
+}
@@ -0,0 +1,212 @@
+use std::{
+    collections::BTreeMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+// arrival at a screen and the choice that led there
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct Step {
+    pub(crate) screen_no: usize,
+    pub(crate) choice: Option<String>, // None for the first screen of the game
+    pub(crate) at: u64,                // milliseconds since the unix epoch
+}
+
+// time the game spent in a save slot instead of being played
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct Pause {
+    pub(crate) from: u64,          // when the game was saved
+    pub(crate) until: Option<u64>, // when it was loaded again, None while it is saved
+}
+
+impl Pause {
+    fn overlap(&self, from: u64, until: u64, now: u64) -> u64 {
+        let end = self.until.unwrap_or(now).min(until);
+        end.saturating_sub(self.from.max(from))
+    }
+}
+
+// the player's journey through the story in the order it happened
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Timeline {
+    pub(crate) steps: Vec<Step>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) pauses: Vec<Pause>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+impl Timeline {
+    pub(crate) fn record(&mut self, screen_no: usize, choice: Option<&str>) {
+        self.steps.push(Step {
+            screen_no,
+            choice: choice.map(|c| c.trim().to_string()),
+            at: now_ms(),
+        });
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.steps.clear();
+        self.pauses.clear();
+    }
+
+    // stop the clock, the game is being saved
+    pub(crate) fn pause(&mut self) {
+        if self.pauses.last().is_none_or(|p| p.until.is_some()) {
+            self.pauses.push(Pause {
+                from: now_ms(),
+                until: None,
+            });
+        }
+    }
+
+    // start the clock again, the game was loaded
+    pub(crate) fn resume(&mut self) {
+        if let Some(pause) = self.pauses.last_mut().filter(|p| p.until.is_none()) {
+            pause.until = Some(now_ms());
+        }
+    }
+
+    // time spent on each step, the last one lasts until now, without the time the
+    // game spent saved
+    pub(crate) fn durations(&self) -> Vec<Duration> {
+        let now = now_ms();
+        self.steps
+            .iter()
+            .enumerate()
+            .map(|(i, step)| {
+                let until = self.steps.get(i + 1).map(|s| s.at).unwrap_or(now);
+                let paused = self
+                    .pauses
+                    .iter()
+                    .map(|p| p.overlap(step.at, until, now))
+                    .sum::<u64>();
+                Duration::from_millis(until.saturating_sub(step.at).saturating_sub(paused))
+            })
+            .collect()
+    }
+
+    // time played from the start of the game to each step, without the time the
+    // game spent saved
+    pub(crate) fn offsets(&self) -> Vec<Duration> {
+        self.durations()
+            .into_iter()
+            .scan(Duration::ZERO, |played, spent| {
+                let at = *played;
+                *played += spent;
+                Some(at)
+            })
+            .collect()
+    }
+
+    // total time spent on each screen over all visits
+    pub(crate) fn time_per_screen(&self) -> BTreeMap<usize, Duration> {
+        let mut times = BTreeMap::new();
+        for (step, duration) in self.steps.iter().zip(self.durations()) {
+            *times.entry(step.screen_no).or_default() += duration;
+        }
+        times
+    }
+
+    pub(crate) fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.steps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(screen_no: usize, at: u64) -> Step {
+        Step {
+            screen_no,
+            choice: None,
+            at,
+        }
+    }
+
+    #[test]
+    fn a_saved_game_stops_the_clock() {
+        let timeline = Timeline {
+            steps: vec![step(0, 1_000), step(1, 3_000)],
+            pauses: vec![Pause {
+                from: 4_500,
+                until: None,
+            }],
+        };
+        assert_eq!(
+            timeline.durations(),
+            [Duration::from_millis(2_000), Duration::from_millis(1_500)]
+        );
+    }
+
+    #[test]
+    fn time_in_the_save_slot_is_not_counted() {
+        let timeline = Timeline {
+            steps: vec![step(0, 1_000), step(1, 10_000), step(0, 12_000)],
+            pauses: vec![
+                Pause {
+                    from: 2_000,
+                    until: Some(9_000),
+                },
+                Pause {
+                    from: 13_000,
+                    until: None,
+                },
+            ],
+        };
+        assert_eq!(
+            timeline.durations(),
+            [2_000, 2_000, 1_000].map(Duration::from_millis)
+        );
+        assert_eq!(
+            timeline.time_per_screen(),
+            BTreeMap::from([
+                (0, Duration::from_millis(3_000)),
+                (1, Duration::from_millis(2_000))
+            ])
+        );
+    }
+
+    #[test]
+    fn time_in_the_save_slot_does_not_move_the_steps() {
+        let timeline = Timeline {
+            steps: vec![step(0, 1_000), step(1, 10_000), step(0, 12_000)],
+            pauses: vec![Pause {
+                from: 2_000,
+                until: Some(9_000),
+            }],
+        };
+        assert_eq!(
+            timeline.offsets(),
+            [0, 2_000, 4_000].map(Duration::from_millis)
+        );
+        assert!(Timeline::default().offsets().is_empty());
+    }
+
+    #[test]
+    fn pause_and_resume_close_each_other() {
+        let mut timeline = Timeline::default();
+        timeline.record(0, None);
+        timeline.resume();
+        assert!(timeline.pauses.is_empty(), "nothing to resume");
+        timeline.pause();
+        timeline.pause();
+        assert_eq!(timeline.pauses.len(), 1);
+        assert_eq!(timeline.pauses[0].until, None);
+        timeline.resume();
+        assert!(
+            timeline.pauses[0]
+                .until
+                .is_some_and(|u| u >= timeline.pauses[0].from)
+        );
+        timeline.clear();
+        assert_eq!(timeline, Timeline::default());
+    }
+}
@@ -1,49 +1,75 @@
-use crate::engine::{config, fs};
+use std::path::Path;
 
-static SCREENS_MISSING: [usize; 11] = [5, 13, 16, 27, 38, 49, 61, 71, 82, 93, 104];
+use crate::engine::{config, fs, story::Story};
 
-pub fn validate_screens(config: &config::Config) -> Result<(), Box<dyn std::error::Error>> {
+// problem with the data files of a single screen
+pub(crate) struct ScreenError {
+    pub(crate) screen_no: usize,
+    pub(crate) message: String,
+}
+
+impl ScreenError {
+    fn new(screen_no: usize, message: String) -> Self {
+        Self { screen_no, message }
+    }
+}
+
+// checks that the text, actions and images of every screen of the story fit together
+pub(crate) fn check_screens(
+    story: &Story,
+    config: &config::Config,
+) -> Result<Vec<ScreenError>, Box<dyn std::error::Error>> {
     let mut errors = Vec::new();
-    for screen_no in 0..=111 {
-        if SCREENS_MISSING.contains(&screen_no) {
-            continue;
-        }
+    if !story.contains(story.start) {
+        errors.push(ScreenError::new(
+            story.start,
+            "start screen is not part of the story".to_string(),
+        ));
+    }
+    for &screen_no in &story.screens {
         let text_path = format!("{}/text/{}.txt", config.data_path, screen_no);
         let actions_path = format!("{}/actions/{}.json", config.data_path, screen_no);
         let images = fs::get_image_names_for_screen(screen_no, config)?;
         if images.is_empty() {
-            errors.push(format!("No images found for screen {}", screen_no));
+            errors.push(ScreenError::new(screen_no, "no images found".to_string()));
         }
-        if !std::path::Path::new(&text_path).exists() {
-            errors.push(format!(
-                "Missing text file for screen {}: {}",
-                screen_no, text_path
+        if !Path::new(&text_path).exists() {
+            errors.push(ScreenError::new(
+                screen_no,
+                format!("missing text file {}", text_path),
             ));
         }
-        if !std::path::Path::new(&actions_path).exists() {
-            errors.push(format!(
-                "Missing actions file for screen {}: {}",
-                screen_no, actions_path
+        if !Path::new(&actions_path).exists() {
+            errors.push(ScreenError::new(
+                screen_no,
+                format!("missing actions file {}", actions_path),
+            ));
+        } else if let Err(e) = fs::read_actions(screen_no, config) {
+            errors.push(ScreenError::new(
+                screen_no,
+                format!("can't read actions file {}: {}", actions_path, e),
             ));
-        }
-        for image_path in images {
-            if !std::path::Path::new(&image_path).exists() {
-                println!(
-                    "Missing image file for screen {}: {}",
-                    screen_no, image_path
-                );
-                errors.push(format!(
-                    "Missing image file for screen {}: {}",
-                    screen_no, image_path
-                ));
-            }
         }
     }
+    errors.sort_by_key(|e| e.screen_no);
+    Ok(errors)
+}
+
+pub fn validate_screens(
+    story: &Story,
+    config: &config::Config,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let errors = check_screens(story, config)?;
     if !errors.is_empty() {
-        for error in errors {
-            eprintln!("{}", error);
+        for error in &errors {
+            eprintln!("screen {}: {}", error.screen_no, error.message);
         }
-        return Err("Validation failed".into());
+        return Err(format!(
+            "Validation of \"{}\" failed, {} problem(s)",
+            story.title,
+            errors.len()
+        )
+        .into());
     }
     Ok(())
 }
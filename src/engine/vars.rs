@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+// story variables - flags and counters set by the player's choices,
+// a variable that was never set reads as 0
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Vars {
+    values: HashMap<String, i64>,
+}
+
+// condition over the story variables and inventory, e.g. `{"gte": ["clues_found", 2]}`
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Condition {
+    IsSet(String),
+    NotSet(String),
+    Eq(String, i64),
+    Gte(String, i64),
+    Lte(String, i64),
+    Has(String),   // the item or clue is in the inventory
+    Lacks(String), // the item or clue is not in the inventory
+    Not(Box<Condition>),
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+}
+
+// change of the story variables or inventory, e.g. `{"add": ["clues_found", 1]}`
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Effect {
+    Set(String),      // set a flag (to 1)
+    Add(String, i64), // increment a counter, negative values decrement
+    Clear(String),    // reset back to 0
+    Give(String),     // put an item or clue into the inventory
+    Take(String),     // remove an item or clue from the inventory
+}
+
+impl Vars {
+    pub fn get(&self, name: &str) -> i64 {
+        self.values.get(name).copied().unwrap_or(0)
+    }
+
+    pub fn set(&mut self, name: &str, value: i64) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    pub fn add(&mut self, name: &str, value: i64) {
+        *self.values.entry(name.to_string()).or_insert(0) += value;
+    }
+
+    pub fn clear(&mut self, name: &str) {
+        self.values.remove(name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::progress::Progress;
+
+    fn condition(json: &str) -> Condition {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn effects(json: &str) -> Vec<Effect> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn unset_variables_read_as_zero() {
+        let mut vars = Vars::default();
+        assert_eq!(vars.get("clues_found"), 0);
+        vars.add("clues_found", 2);
+        vars.add("clues_found", -1);
+        assert_eq!(vars.get("clues_found"), 1);
+        vars.clear("clues_found");
+        assert_eq!(vars, Vars::default());
+    }
+
+    #[test]
+    fn effects_change_variables_and_inventory() {
+        let mut progress = Progress::default();
+        progress.apply(&effects(
+            r#"[{"set": "met_angell"}, {"add": ["clues_found", 2]}, {"give": "revolver"},
+                {"add": ["clues_found", -1]}, {"give": "lamp"}, {"take": "lamp"}]"#,
+        ));
+        assert_eq!(progress.vars.get("met_angell"), 1);
+        assert_eq!(progress.vars.get("clues_found"), 1);
+        assert!(progress.inventory.has("revolver"));
+        assert!(!progress.inventory.has("lamp"));
+        progress.apply(&effects(r#"[{"clear": "met_angell"}]"#));
+        assert_eq!(progress.vars.get("met_angell"), 0);
+    }
+
+    #[test]
+    fn conditions_are_evaluated_against_the_progress() {
+        let mut progress = Progress::default();
+        progress.apply(&effects(
+            r#"[{"set": "met_angell"}, {"add": ["clues_found", 2]}, {"give": "revolver"}]"#,
+        ));
+        let holds = |json| progress.check(&condition(json));
+        assert!(holds(r#"{"is_set": "met_angell"}"#));
+        assert!(holds(r#"{"not_set": "met_legrasse"}"#));
+        assert!(holds(r#"{"eq": ["clues_found", 2]}"#));
+        assert!(holds(r#"{"gte": ["clues_found", 2]}"#));
+        assert!(!holds(r#"{"gte": ["clues_found", 3]}"#));
+        assert!(holds(r#"{"lte": ["unknown", 0]}"#));
+        assert!(holds(r#"{"has": "revolver"}"#));
+        assert!(holds(r#"{"lacks": "newspaper_clippings"}"#));
+        assert!(!holds(r#"{"not": {"has": "revolver"}}"#));
+        assert!(holds(
+            r#"{"all": [{"is_set": "met_angell"}, {"has": "revolver"}]}"#
+        ));
+        assert!(!holds(
+            r#"{"all": [{"is_set": "met_angell"}, {"is_set": "met_legrasse"}]}"#
+        ));
+        assert!(holds(
+            r#"{"any": [{"is_set": "met_legrasse"}, {"has": "revolver"}]}"#
+        ));
+        assert!(!holds(r#"{"any": []}"#));
+    }
+}
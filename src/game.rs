@@ -1,117 +1,572 @@
-use std::error::Error;
+use std::{error::Error, io::Stdout};
 
 use crossterm::event::{self, Event};
+use ratatui::{Terminal, layout::Size, prelude::CrosstermBackend};
 
 use crate::{
     engine::{
+        achievements::Achievements,
         config,
+        dice::Dice,
+        fs,
         gfx::{self, ScreenRenderer},
+        graphics::{self, ImageOutput},
+        image_cache::ImageCache,
+        investigator::Investigator,
+        lint,
+        progress::Progress,
+        sanity, save,
+        story::Story,
         validate,
     },
     screens::{
-        achievements, graph, intro_screen,
+        achievements, graph,
+        graph_view::GraphView,
+        intro_screen,
+        investigator::{InvestigatorEvent, InvestigatorScreen},
+        journey::{JourneyEvent, JourneyScreen},
         play::{self, GameEvent, GameState},
+        slots::{self, SlotEvent, SlotMode},
     },
 };
 
+// unlock achievements earned by the latest move and remember them across runs,
+// a failure to remember them is shown on the screen rather than ending the game
+fn check_achievements(
+    achievements: &mut Achievements,
+    game_graph: &graph::GameGraph,
+    progress: &mut Progress,
+    cfg: &config::Config,
+) {
+    let unlocked = achievements.evaluate(game_graph);
+    if unlocked.is_empty() {
+        return;
+    }
+    if let Err(e) = achievements.persist(cfg) {
+        let failure = format!("Achievements could not be saved: {}", e);
+        progress.notice = Some(match progress.notice.take() {
+            Some(notice) => format!("{}\n{}", notice, failure),
+            None => failure,
+        });
+    }
+    if cfg.notifications.unwrap_or(false) {
+        for achievement in unlocked {
+            play::send_notification(&format!("Achievement unlocked: {}", achievement.title));
+        }
+    }
+}
+
+// everything that happens when the player arrives at a screen
+fn enter_screen(
+    screen_no: usize,
+    choice: Option<&str>,
+    game_graph: &mut graph::GameGraph,
+    progress: &mut Progress,
+    dice: &mut Dice,
+    cfg: &config::Config,
+) {
+    progress.notice = None;
+    progress.redirect = None;
+    progress.insanity.next_screen();
+    if let Ok(actions) = fs::read_actions(screen_no, cfg) {
+        progress.apply(&actions.on_enter);
+        if let Some(check) = &actions.sanity {
+            progress.redirect = sanity::apply(check, progress, dice);
+        }
+    }
+    game_graph.visit(screen_no, choice);
+}
+
+// the state before a choice was taken, so that it can be taken back
+struct Undo {
+    screen_no: usize,
+    selected: usize, // place of the choice in the menu
+    progress: Progress,
+    dice: Dice, // as they were before the screen got its own, so rolls come out the same again
+}
+
+// dice for a new play screen, `forked_from` remembers where they came from for undo
+fn screen_dice(dice: &mut Dice, forked_from: &mut Dice) -> Dice {
+    *forked_from = dice.clone();
+    dice.fork()
+}
+
+// follow a choice from the current screen, returns how to take it back
+fn take_choice(
+    choice: &play::Choice,
+    selected: usize,
+    game_graph: &mut graph::GameGraph,
+    progress: &mut Progress,
+    dice: &mut Dice,
+    forked_from: &Dice,
+    cfg: &config::Config,
+) -> Option<Undo> {
+    let undo = game_graph.current_screen().map(|screen_no| Undo {
+        screen_no,
+        selected,
+        progress: progress.clone(),
+        dice: forked_from.clone(),
+    });
+    progress.apply(&choice.effects);
+    enter_screen(
+        choice.next,
+        Some(&choice.text),
+        game_graph,
+        progress,
+        dice,
+        cfg,
+    );
+    undo
+}
+
+// take back the last choice, returns the screen to show again and the choice to highlight
+fn take_back(
+    history: &mut Vec<Undo>,
+    game_graph: &mut graph::GameGraph,
+    progress: &mut Progress,
+    dice: &mut Dice,
+) -> Option<(usize, usize)> {
+    let undo = history.pop()?;
+    game_graph.unvisit();
+    *progress = undo.progress;
+    *dice = undo.dice;
+    Some((undo.screen_no, undo.selected))
+}
+
+// continue the game stored in the given save slot, returns the screen to show
+fn resume(
+    slot: save::SaveGame,
+    game_graph: &mut graph::GameGraph,
+    progress: &mut Progress,
+) -> usize {
+    game_graph.restore(slot.visited, slot.path, slot.timeline);
+    *progress = slot.progress;
+    slot.screen_no
+}
+
+// runs the game with the settings of config.json
 pub fn play() -> Result<(), Box<dyn Error>> {
-    let cfg = config::Config::load()?;
+    play_with(config::Config::load()?, None)
+}
+
+// runs the game, starting new games on the given screen instead of the story's start
+pub(crate) fn play_with(cfg: config::Config, screen: Option<usize>) -> Result<(), Box<dyn Error>> {
+    let story = Story::load(&cfg)?;
     // validate data files
-    validate::validate_screens(&cfg)?;
+    validate::validate_screens(&story, &cfg)?;
     // load screen graph
-    let mut game_graph = graph::GameGraph::load(&cfg);
+    let game_graph = graph::GameGraph::load(&story, &cfg);
+    // refuse to start a story that can't be played through
+    lint::check(&game_graph, &story)?;
+    let graph_view = GraphView::new(&game_graph, story.start);
+    let achievements = Achievements::load(&cfg)?;
+    let current_screen = screen.unwrap_or_else(|| cfg.get_screen(story.start));
+    if !story.contains(current_screen) {
+        return Err(format!("screen {} is not part of the story", current_screen).into());
+    }
     // Initialize terminal
     let mut terminal = gfx::init()?;
+    let result = run(
+        &mut terminal,
+        cfg,
+        game_graph,
+        graph_view,
+        achievements,
+        current_screen,
+    );
+    // Restore terminal, also when the game failed
+    gfx::shutdown(terminal)?;
+    result
+}
 
-    let dim = terminal.size()?;
+// the game loop, returns when the player exits from the intro menu
+fn run(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    cfg: config::Config,
+    mut game_graph: graph::GameGraph,
+    mut graph_view: GraphView,
+    mut achievements: Achievements,
+    current_screen: usize,
+) -> Result<(), Box<dyn Error>> {
+    let mut dim = terminal.size()?;
     //println!("Terminal size: {}x{}", dim.width, dim.height);
 
-    let current_screen = cfg.get_screen();
-    game_graph.visit(current_screen);
+    // investigator used for the next new game, changed on the investigator screen
+    let mut investigator = Investigator::default();
+    let mut progress = Progress::new(investigator.clone());
+    let mut dice = Dice::new(cfg.dice_seed);
+    let mut forked_from = dice.clone();
+    // block characters or a terminal graphics protocol
+    let mut image_output = ImageOutput::new(graphics::backend(&cfg));
+    // decoded illustrations, the next screens' ones are prepared in the background
+    let images = ImageCache::new(image_output.cell_size());
     let mut screen: Box<dyn ScreenRenderer<GameEvent>> = Box::new(play::PlayScreen::new(
         current_screen,
-        dim.width,
-        dim.height,
+        dim,
         &cfg,
+        &progress,
+        screen_dice(&mut dice, &mut forked_from),
+        &images,
+        false,
     )?);
 
     let mut state = GameState::Intro;
+    let mut history: Vec<Undo> = Vec::new();
 
     let mut intro_screen =
         intro_screen::IntroScreen::new(dim.width as usize, dim.height as usize, &cfg)?;
     let mut achievements_screen =
         achievements::AchievementScreen::new(dim.width as usize, dim.height as usize, &cfg)?;
+    let mut slots_screen = slots::SlotScreen::new(dim.width as usize, dim.height as usize, &cfg)?;
+    let mut journey_screen = JourneyScreen::new();
+    let mut investigator_screen =
+        InvestigatorScreen::new(dim.width as usize, dim.height as usize, &cfg)?;
 
     loop {
-        if event::poll(std::time::Duration::from_millis(5))? {
-            if let Event::Key(key_event) = event::read()? {
-                // if key_event.code == crossterm::event::KeyCode::Esc {
-                //     break;
-                // }
-
-                match state {
-                    GameState::Playing => {
-                        if let Some(event) = screen.key_event(key_event.code) {
-                            match event {
-                                GameEvent::NewScreen(screen_no) => {
-                                    game_graph.visit(screen_no);
-                                    screen = Box::new(play::PlayScreen::new(
-                                        screen_no, dim.width, dim.height, &cfg,
-                                    )?);
+        let event = if event::poll(std::time::Duration::from_millis(5))? {
+            Some(event::read()?)
+        } else {
+            None
+        };
+        if let Some(Event::Resize(width, height)) = event {
+            // the cached images were scaled for the old size
+            dim = Size::new(width, height);
+            images.clear();
+            if image_output.clear(terminal.backend_mut())? {
+                terminal.clear()?;
+            }
+            screen.resize(width, height);
+        }
+        if let Some(Event::Key(key_event)) = event {
+            // if key_event.code == crossterm::event::KeyCode::Esc {
+            //     break;
+            // }
+
+            match state {
+                GameState::Playing => {
+                    if let Some(event) = screen.key_event(key_event.code) {
+                        match event {
+                            GameEvent::NewScreen(choice, selected) => {
+                                history.extend(take_choice(
+                                    &choice,
+                                    selected,
+                                    &mut game_graph,
+                                    &mut progress,
+                                    &mut dice,
+                                    &forked_from,
+                                    &cfg,
+                                ));
+                                check_achievements(
+                                    &mut achievements,
+                                    &game_graph,
+                                    &mut progress,
+                                    &cfg,
+                                );
+                                screen = Box::new(play::PlayScreen::new(
+                                    choice.next,
+                                    dim,
+                                    &cfg,
+                                    &progress,
+                                    screen_dice(&mut dice, &mut forked_from),
+                                    &images,
+                                    game_graph.seen_before(choice.next),
+                                )?);
+                            }
+                            GameEvent::Back => {
+                                if !cfg.hardcore.unwrap_or(false)
+                                    && let Some((screen_no, selected)) = take_back(
+                                        &mut history,
+                                        &mut game_graph,
+                                        &mut progress,
+                                        &mut dice,
+                                    )
+                                {
+                                    let mut play_screen = play::PlayScreen::new(
+                                        screen_no,
+                                        dim,
+                                        &cfg,
+                                        &progress,
+                                        screen_dice(&mut dice, &mut forked_from),
+                                        &images,
+                                        true,
+                                    )?;
+                                    play_screen.select_choice(selected);
+                                    screen = Box::new(play_screen);
                                 }
-                                GameEvent::Exit => state = GameState::Intro,
-                                GameEvent::Ending => state = GameState::Ending,
+                            }
+                            GameEvent::Exit => state = GameState::Intro,
+                            GameEvent::Ending => {
+                                if let Some(screen_no) = game_graph.current_screen() {
+                                    graph_view.focus_on(screen_no);
+                                }
+                                journey_screen.open();
+                                state = GameState::Journey;
                             }
                         }
                     }
-                    GameState::Ending => {
-                        // do nothing, just show the graph
-                        let game_event = game_graph.key_event(key_event.code);
-                        if game_event == Some(GameEvent::Exit) {
-                            state = GameState::Intro;
-                        }
+                }
+                GameState::Journey => match journey_screen
+                    .key_event(key_event.code, game_graph.timeline.steps.len())
+                {
+                    Some(JourneyEvent::Exit) => state = GameState::Intro,
+                    Some(JourneyEvent::Graph) => state = GameState::Ending,
+                    Some(JourneyEvent::Export) => {
+                        let status = match save::export_timeline(&cfg, &game_graph.timeline) {
+                            Ok(path) => format!("Exported to {}", path.display()),
+                            Err(e) => format!("Export failed: {}", e),
+                        };
+                        journey_screen.set_status(status);
+                    }
+                    None => {}
+                },
+                GameState::Ending => {
+                    // just show the graph
+                    let game_event = graph_view.key_event(key_event.code);
+                    if game_event == Some(GameEvent::Exit) {
+                        state = GameState::Intro;
+                    }
+                }
+                GameState::Investigator => match investigator_screen.key_event(key_event.code) {
+                    Some(InvestigatorEvent::Accept(chosen)) => {
+                        investigator = chosen;
+                        state = GameState::Intro;
                     }
-                    GameState::Achievements => {
-                        let achievement_event = achievements_screen.key_event(key_event.code);
-                        if achievement_event == Some(GameEvent::Exit) {
+                    Some(InvestigatorEvent::Cancel) => state = GameState::Intro,
+                    None => {}
+                },
+                GameState::Achievements => {
+                    let achievement_event = achievements_screen.key_event(key_event.code);
+                    if achievement_event == Some(GameEvent::Exit) {
+                        state = GameState::Intro;
+                    }
+                }
+                GameState::Slots => match slots_screen.key_event(key_event.code) {
+                    Some(SlotEvent::Exit) => state = GameState::Intro,
+                    Some(SlotEvent::Save(name)) => {
+                        if let Some(screen_no) = game_graph.current_screen() {
+                            let slot = save::SaveGame::new(
+                                &name,
+                                screen_no,
+                                &game_graph.visited,
+                                &game_graph.path,
+                                &game_graph.timeline,
+                                &progress,
+                            );
+                            // stay on the slots so the player can try again or elsewhere
+                            match save::write(&cfg, &slot) {
+                                Ok(()) => state = GameState::Intro,
+                                Err(e) => slots_screen.set_status(format!("Save failed: {}", e)),
+                            }
+                        } else {
                             state = GameState::Intro;
                         }
                     }
-                    GameState::Intro => {
-                        // do nothing, just show the intro screen
-                        intro_screen.key_event(key_event.code);
-                        let intro_event = intro_screen.get_selected_item();
-                        if intro_event == Some(intro_screen::NEW_GAME) {
+                    Some(SlotEvent::Load(name)) => {
+                        if let Some(slot) = slots_screen.get_slot(&name) {
+                            history.clear();
+                            let screen_no = resume(slot, &mut game_graph, &mut progress);
+                            // the save may come from before the achievement existed
+                            check_achievements(&mut achievements, &game_graph, &mut progress, &cfg);
+                            screen = Box::new(play::PlayScreen::new(
+                                screen_no,
+                                dim,
+                                &cfg,
+                                &progress,
+                                screen_dice(&mut dice, &mut forked_from),
+                                &images,
+                                true,
+                            )?);
                             state = GameState::Playing;
+                        }
+                    }
+                    None => {}
+                },
+                GameState::Intro => {
+                    // do nothing, just show the intro screen
+                    intro_screen.key_event(key_event.code);
+                    let intro_event = intro_screen.get_selected_item();
+                    if intro_event == Some(intro_screen::NEW_GAME) {
+                        state = GameState::Playing;
+                        game_graph.reset();
+                        history.clear();
+                        progress = Progress::new(investigator.clone());
+                        enter_screen(
+                            current_screen,
+                            None,
+                            &mut game_graph,
+                            &mut progress,
+                            &mut dice,
+                            &cfg,
+                        );
+                        check_achievements(&mut achievements, &game_graph, &mut progress, &cfg);
+                        screen = Box::new(play::PlayScreen::new(
+                            current_screen,
+                            dim,
+                            &cfg,
+                            &progress,
+                            screen_dice(&mut dice, &mut forked_from),
+                            &images,
+                            false,
+                        )?);
+                    } else if intro_event == Some(intro_screen::CONTINUE) {
+                        // resume the most recently saved slot, if any
+                        if let Some(slot) = save::latest(&cfg) {
+                            history.clear();
+                            let screen_no = resume(slot, &mut game_graph, &mut progress);
+                            // the save may come from before the achievement existed
+                            check_achievements(&mut achievements, &game_graph, &mut progress, &cfg);
                             screen = Box::new(play::PlayScreen::new(
-                                current_screen,
-                                dim.width,
-                                dim.height,
+                                screen_no,
+                                dim,
                                 &cfg,
+                                &progress,
+                                screen_dice(&mut dice, &mut forked_from),
+                                &images,
+                                true,
                             )?);
-                        } else if intro_event == Some(intro_screen::ACHIEVEMENTS) {
-                            state = GameState::Achievements;
-                        } else if intro_event == Some(intro_screen::EXIT) {
-                            break;
+                            state = GameState::Playing;
+                        }
+                    } else if intro_event == Some(intro_screen::LOAD) {
+                        slots_screen.open(SlotMode::Load, &cfg);
+                        state = GameState::Slots;
+                    } else if intro_event == Some(intro_screen::SAVE) {
+                        // there is nothing to save before the first game starts
+                        if game_graph.current_screen().is_some() {
+                            slots_screen.open(SlotMode::Save, &cfg);
+                            state = GameState::Slots;
                         }
+                    } else if intro_event == Some(intro_screen::ACHIEVEMENTS) {
+                        state = GameState::Achievements;
+                    } else if intro_event == Some(intro_screen::INVESTIGATOR) {
+                        investigator_screen.open(&investigator);
+                        state = GameState::Investigator;
+                    } else if intro_event == Some(intro_screen::EXIT) {
+                        break;
                     }
                 }
             }
         }
 
+        if state != GameState::Playing {
+            // a graphics protocol image would stay on top of the other screens
+            if image_output.clear(terminal.backend_mut())? {
+                terminal.clear()?;
+            }
+        }
         if state == GameState::Intro {
-            intro_screen.render(&mut terminal)?;
+            intro_screen.render(terminal)?;
+        } else if state == GameState::Investigator {
+            investigator_screen.render(terminal)?;
         } else if state == GameState::Achievements {
-            achievements_screen.render(&mut terminal)?;
+            achievements_screen.render(terminal, &achievements)?;
+        } else if state == GameState::Slots {
+            slots_screen.render(terminal)?;
         } else if state == GameState::Ending {
-            game_graph.render(&mut terminal)?;
+            graph_view.render(terminal, &game_graph)?;
+        } else if state == GameState::Journey {
+            journey_screen.render(terminal, &game_graph)?;
         } else {
-            gfx::render(&mut terminal, &mut screen)?;
+            gfx::render(terminal, &mut screen, &mut image_output)?;
         }
         //println!("State: {:?}", state);
     }
-
-    // Restore terminal
-    gfx::shutdown(terminal)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // roll a skill check on the current screen and follow the choice,
+    // returns the roll, the state after it and what the dice roll next
+    fn roll_and_take(
+        history: &mut Vec<Undo>,
+        game_graph: &mut graph::GameGraph,
+        progress: &mut Progress,
+        dice: &mut Dice,
+        forked_from: &mut Dice,
+        cfg: &config::Config,
+    ) -> (u8, Progress, u8) {
+        let check = screen_dice(dice, forked_from).d100();
+        let choice = play::Choice::new("dál", 32);
+        history.extend(take_choice(
+            &choice,
+            0,
+            game_graph,
+            progress,
+            dice,
+            forked_from,
+            cfg,
+        ));
+        (check, progress.clone(), dice.clone().d100())
+    }
+
+    #[test]
+    fn achievements_that_cant_be_saved_are_reported() {
+        // a file where the save directory should be
+        let blocked =
+            std::env::temp_dir().join(format!("coc-blocked-saves-{}", std::process::id()));
+        std::fs::write(&blocked, "").unwrap();
+        let cfg = config::Config {
+            data_path: "assets/".to_string(),
+            save_path: Some(blocked.to_string_lossy().into_owned()),
+            ..config::Config::default()
+        };
+        let story = Story::load(&cfg).unwrap();
+        let mut game_graph = graph::GameGraph::load(&story, &cfg);
+        let mut achievements = Achievements::load(&cfg).unwrap();
+        let mut progress = Progress {
+            notice: Some("Sanity roll".to_string()),
+            ..Progress::default()
+        };
+        game_graph.visit(story.start, None);
+        game_graph.visit(1, Some("dál"));
+        check_achievements(&mut achievements, &game_graph, &mut progress, &cfg);
+        let notice = progress.notice.unwrap();
+        assert!(notice.starts_with("Sanity roll\nAchievements could not be saved"));
+        std::fs::remove_file(blocked).unwrap();
+    }
+
+    #[test]
+    fn taking_a_choice_again_rolls_the_same() {
+        let cfg = config::Config {
+            data_path: "assets/".to_string(),
+            ..config::Config::default()
+        };
+        let story = Story::load(&cfg).unwrap();
+        let mut game_graph = graph::GameGraph::load(&story, &cfg);
+        let mut progress = Progress::default();
+        let mut dice = Dice::new(None);
+        let mut forked_from = dice.clone();
+        let mut history = Vec::new();
+        enter_screen(
+            story.start,
+            None,
+            &mut game_graph,
+            &mut progress,
+            &mut dice,
+            &cfg,
+        );
+
+        let first = roll_and_take(
+            &mut history,
+            &mut game_graph,
+            &mut progress,
+            &mut dice,
+            &mut forked_from,
+            &cfg,
+        );
+        let back = take_back(&mut history, &mut game_graph, &mut progress, &mut dice);
+        assert_eq!(back, Some((story.start, 0)));
+        let again = roll_and_take(
+            &mut history,
+            &mut game_graph,
+            &mut progress,
+            &mut dice,
+            &mut forked_from,
+            &cfg,
+        );
+        assert_eq!(first, again);
+        assert_eq!(history.len(), 1);
+    }
+}
@@ -1,6 +1,7 @@
 #[macro_use]
 extern crate lazy_static;
-mod effects;
+pub mod cli;
+pub(crate) mod effects;
 pub(crate) mod engine;
 pub mod game;
 pub(crate) mod screens;
@@ -1,7 +1,7 @@
-use call_of_cthulhu::game;
+use call_of_cthulhu::cli;
 
 use std::error::Error;
 
 fn main() -> Result<(), Box<dyn Error>> {
-    game::play()
+    cli::run(std::env::args().skip(1))
 }
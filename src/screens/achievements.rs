@@ -5,29 +5,21 @@ use ratatui::{
     Terminal,
     prelude::CrosstermBackend,
     style::{Color, Modifier, Style},
-    text::Line,
-    widgets::{Block, Borders, List},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState},
 };
 
 use crate::{
-    engine::{config, fs},
+    engine::{achievements::Achievements, config, fs, gfx::Painter, save},
     screens::play::GameEvent,
 };
 
 pub struct AchievementScreen {
-    pub(crate) width: usize,
-    pub(crate) height: usize,
     image: Vec<Vec<(u8, u8, u8)>>,
+    painter: Painter,
+    list_state: ListState,
 }
 
-const ACHIEVEMENTS: [&'static str; 5] = [
-    "Achievement 1: First Steps",
-    "Achievement 2: Explorer",
-    "Achievement 3: Master of Screens",
-    "Achievement 4: Completionist",
-    "Achievement 5: Unlocked All Secrets",
-];
-
 impl AchievementScreen {
     pub fn new(
         width: usize,
@@ -35,41 +27,80 @@ impl AchievementScreen {
         config: &config::Config,
     ) -> Result<Self, Box<dyn std::error::Error>> {
         let image = fs::load_achievements_screen_image(width as u16, height as u16, config)?;
+        let mut list_state = ListState::default();
+        list_state.select(Some(0));
         Ok(Self {
-            width,
-            height,
             image,
+            painter: Painter::new(config),
+            list_state,
         })
     }
 
     pub fn render(
-        &self,
+        &mut self,
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+        achievements: &Achievements,
     ) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(selected) = self.list_state.selected()
+            && selected >= achievements.list.len()
+        {
+            self.list_state
+                .select(achievements.list.len().checked_sub(1));
+        }
+
         terminal.draw(|f| {
             let area = f.area();
 
             // Draw intro image across full terminal
-            for (y, row) in self.image.iter().enumerate() {
-                for (x, (r, g, b)) in row.iter().enumerate() {
-                    if y < area.height as usize && x < area.width as usize {
-                        if let Some(cell) = f.buffer_mut().cell_mut((x as u16, y as u16)) {
-                            cell.set_bg(Color::Rgb(*r, *g, *b));
-                        }
-                    }
-                }
-            }
+            self.painter.draw(f.buffer_mut(), area, &self.image);
 
-            let list = List::new(ACHIEVEMENTS.iter().map(|a| Line::from(*a)))
-                .block(Block::default().borders(Borders::ALL).title("Achievements"))
+            let items = achievements
+                .list
+                .iter()
+                .map(|a| match achievements.unlocked_at(&a.id) {
+                    Some(when) => ListItem::new(vec![
+                        Line::from(vec![
+                            Span::styled(
+                                format!("[x] {}", a.title),
+                                Style::default()
+                                    .fg(Color::Green)
+                                    .add_modifier(Modifier::BOLD),
+                            ),
+                            Span::styled(
+                                format!("  unlocked {}", save::format_timestamp(when)),
+                                Style::default().fg(Color::Gray),
+                            ),
+                        ]),
+                        Line::from(format!("    {}", a.description)),
+                    ]),
+                    None if a.hidden => ListItem::new(vec![
+                        Line::from("[ ] ???"),
+                        Line::from("    Hidden achievement"),
+                    ])
+                    .style(Style::default().fg(Color::DarkGray)),
+                    None => ListItem::new(vec![
+                        Line::from(format!("[ ] {}", a.title)),
+                        Line::from(format!("    {}", a.description)),
+                    ])
+                    .style(Style::default().fg(Color::DarkGray)),
+                });
+
+            let title = format!(
+                "Achievements ({}/{})",
+                achievements
+                    .list
+                    .iter()
+                    .filter(|a| achievements.unlocked_at(&a.id).is_some())
+                    .count(),
+                achievements.list.len()
+            );
+            let list = List::new(items)
+                .block(Block::default().borders(Borders::ALL).title(title))
                 .style(Style::default().fg(Color::White))
-                .highlight_style(
-                    Style::default()
-                        .fg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD),
-                );
+                .highlight_symbol("> ");
 
-            f.render_widget(list, area);
+            f.render_stateful_widget(list, area, &mut self.list_state.clone());
+            self.painter.restore(f.buffer_mut(), area, &self.image);
         })?;
 
         Ok(())
@@ -80,6 +111,14 @@ impl AchievementScreen {
             KeyCode::Enter | KeyCode::Esc => {
                 Some(GameEvent::Exit) // just exit the graph view on Enter
             }
+            KeyCode::Up => {
+                self.list_state.select_previous();
+                None
+            }
+            KeyCode::Down => {
+                self.list_state.select_next();
+                None
+            }
             _ => None,
         }
     }
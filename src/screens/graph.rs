@@ -1,117 +1,148 @@
-use std::{collections::HashMap, io};
-
-use crossterm::event::KeyCode;
-use ratatui::{Terminal, prelude::CrosstermBackend};
+use std::collections::{HashMap, HashSet};
 
 use crate::{
-    engine::{config, fs},
-    screens::play::GameEvent,
+    engine::{config, fs, story::Story, timeline::Timeline},
+    screens::play::Choice,
 };
 
 pub struct GameGraph {
     pub(crate) graph: HashMap<usize, Vec<usize>>,
     pub(crate) visited: HashMap<usize, usize>, // how many times each screen was visited
+    pub(crate) path: Vec<usize>,               // screens in the order the player went through them
+    pub(crate) endings: HashSet<usize>,        // screens that end the story
+    pub(crate) locations: HashMap<usize, String>, // screens that announce a new location
+    pub(crate) choices: HashMap<usize, Vec<Choice>>, // choices declared by each screen
+    pub(crate) timeline: Timeline,             // when the player got where and by which choice
+    pub(crate) insanity: HashMap<usize, Vec<(usize, String)>>, // where sanity rolls send the player
 }
 
 impl GameGraph {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {
             graph: HashMap::new(),
             visited: HashMap::new(),
+            path: Vec::new(),
+            endings: HashSet::new(),
+            locations: HashMap::new(),
+            choices: HashMap::new(),
+            timeline: Timeline::default(),
+            insanity: HashMap::new(),
         }
     }
 
-    fn add_screen(&mut self, screen_no: usize, actions: &[(String, usize)]) {
+    // screens with the targets of their choices, the fixture of the graph tests
+    #[cfg(test)]
+    pub(crate) fn from_targets(screens: &[(usize, &[usize])]) -> Self {
+        let mut graph = Self::new();
+        for (screen_no, targets) in screens {
+            let choices = targets
+                .iter()
+                .map(|&t| Choice::new(&format!("to {}", t), t))
+                .collect::<Vec<_>>();
+            graph.add_screen(*screen_no, &choices, &[]);
+        }
+        graph
+    }
+
+    // `forced` are the screens the player is sent to regardless of the choices,
+    // with the reason, e.g. "temporary insanity"
+    pub(crate) fn add_screen(
+        &mut self,
+        screen_no: usize,
+        actions: &[Choice],
+        forced: &[(usize, String)],
+    ) {
         let next_screens = actions
             .iter()
-            .map(|(_, next_screen)| *next_screen)
+            .flat_map(|choice| choice.targets())
+            .chain(forced.iter().map(|(target, _)| *target))
             .collect();
         self.graph.insert(screen_no, next_screens);
+        self.choices.insert(screen_no, actions.to_vec());
+        if !forced.is_empty() {
+            self.insanity.insert(screen_no, forced.to_vec());
+        }
     }
 
-    pub fn visit(&mut self, screen_no: usize) {
+    pub fn visit(&mut self, screen_no: usize, choice: Option<&str>) {
         self.visited
             .entry(screen_no)
             .and_modify(|v| *v += 1)
             .or_insert(1);
+        self.path.push(screen_no);
+        self.timeline.record(screen_no, choice);
     }
 
-    pub fn load(config: &config::Config) -> Self {
-        let mut g = Self::new();
-        for screen_no in 0..=111 {
-            if let Ok(actions) = fs::read_actions(screen_no, config) {
-                g.add_screen(screen_no, &actions.next.into_iter().collect::<Vec<_>>());
+    // take back the latest visit, the undo of `visit`
+    pub fn unvisit(&mut self) {
+        let Some(screen_no) = self.path.pop() else {
+            return;
+        };
+        if let Some(count) = self.visited.get_mut(&screen_no) {
+            *count -= 1;
+            if *count == 0 {
+                self.visited.remove(&screen_no);
             }
         }
-        g
+        self.timeline.steps.pop();
     }
 
-    pub fn render(
-        &self,
-        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        terminal.draw(|f| {
-            let area = f.area();
-            let block = ratatui::widgets::Block::default()
-                .title("Game Graph")
-                .borders(ratatui::widgets::Borders::ALL);
-            f.render_widget(block, area);
-
-            let mut y = 1;
-            let mut x = 1;
-            let mut col_width = area.width / 2 - 2;
+    // forget the progress of the previous game
+    pub fn reset(&mut self) {
+        self.visited.clear();
+        self.path.clear();
+        self.timeline.clear();
+    }
 
-            for (screen_no, next_screens) in &self.graph {
-                if y >= area.height - 1 && x == 1 {
-                    x = (area.width / 2) + 1;
-                    y = 1;
-                }
+    // continue with progress loaded from a save slot
+    pub fn restore(
+        &mut self,
+        visited: HashMap<usize, usize>,
+        path: Vec<usize>,
+        timeline: Timeline,
+    ) {
+        self.visited = visited;
+        self.path = path;
+        self.timeline = timeline;
+        self.timeline.resume();
+    }
 
-                let visit_count = self.visited.get(screen_no).copied().unwrap_or(0);
-                let style = if visit_count == 0 {
-                    ratatui::style::Style::default().fg(ratatui::style::Color::DarkGray)
-                } else {
-                    let color = match visit_count {
-                        1..=2 => ratatui::style::Color::Blue,
-                        3..=5 => ratatui::style::Color::Cyan,
-                        6..=10 => ratatui::style::Color::Green,
-                        11..=20 => ratatui::style::Color::Yellow,
-                        21..=50 => ratatui::style::Color::Magenta,
-                        _ => ratatui::style::Color::Red,
-                    };
-                    ratatui::style::Style::default().fg(color)
-                };
+    // the player has been on the screen before the current visit
+    pub fn seen_before(&self, screen_no: usize) -> bool {
+        self.visited.get(&screen_no).is_some_and(|v| *v > 1)
+    }
 
-                let text = if visit_count == 0 {
-                    "".to_string()
-                } else {
-                    format!(
-                        "Screen {}: {:?} ({}x)",
-                        screen_no, next_screens, visit_count
-                    )
-                };
-                let line = ratatui::text::Line::raw(text).style(style);
-                f.render_widget(
-                    ratatui::widgets::Paragraph::new(line),
-                    ratatui::layout::Rect {
-                        x,
-                        y,
-                        width: col_width,
-                        height: 1,
-                    },
-                );
-                y += 1;
-            }
-        })?;
-        Ok(())
+    pub fn current_screen(&self) -> Option<usize> {
+        self.path.last().copied()
     }
 
-    pub fn key_event(&mut self, key_code: KeyCode) -> Option<GameEvent> {
-        match key_code {
-            KeyCode::Enter => {
-                Some(GameEvent::Exit) // just exit the graph view on Enter
+    pub(crate) fn load(story: &Story, config: &config::Config) -> Self {
+        let mut g = Self::new();
+        for &screen_no in &story.screens {
+            if let Ok(actions) = fs::read_actions(screen_no, config) {
+                if actions.ending.unwrap_or(false) {
+                    g.endings.insert(screen_no);
+                }
+                if let Some(location) = &actions.location {
+                    g.locations.insert(screen_no, location.clone());
+                }
+                // insanity can take the player elsewhere regardless of the choices
+                let mut forced = Vec::new();
+                if let Some(sanity) = &actions.sanity {
+                    forced.extend(
+                        sanity
+                            .temporary
+                            .map(|t| (t, "temporary insanity".to_string())),
+                    );
+                    forced.extend(
+                        sanity
+                            .indefinite
+                            .map(|t| (t, "indefinite insanity".to_string())),
+                    );
+                }
+                g.add_screen(screen_no, &actions.choices(), &forced);
             }
-            _ => None,
         }
+        g
     }
 }
@@ -0,0 +1,824 @@
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet, VecDeque},
+    io,
+};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    Terminal,
+    layout::{Constraint, Direction, Layout},
+    prelude::CrosstermBackend,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::screens::{graph::GameGraph, play::GameEvent};
+
+// directions a line leaves a canvas cell in
+const UP: u8 = 1;
+const DOWN: u8 = 2;
+const LEFT: u8 = 4;
+const RIGHT: u8 = 8;
+
+// box drawing character joining the given directions
+fn glyph(mask: u8) -> char {
+    match mask {
+        m if m == UP | DOWN | LEFT | RIGHT => '┼',
+        m if m == UP | DOWN | RIGHT => '├',
+        m if m == UP | DOWN | LEFT => '┤',
+        m if m == DOWN | LEFT | RIGHT => '┬',
+        m if m == UP | LEFT | RIGHT => '┴',
+        m if m == DOWN | RIGHT => '┌',
+        m if m == DOWN | LEFT => '┐',
+        m if m == UP | RIGHT => '└',
+        m if m == UP | LEFT => '┘',
+        m if m & (LEFT | RIGHT) != 0 && m & (UP | DOWN) == 0 => '─',
+        _ => '│',
+    }
+}
+
+// node and gap sizes of one zoom level
+struct Scale {
+    node_w: usize,
+    node_h: usize,
+    col_gap: usize,
+    row_gap: usize,
+}
+
+const SCALES: [Scale; 2] = [
+    // zoomed out, nodes are just their numbers
+    Scale {
+        node_w: 5,
+        node_h: 1,
+        col_gap: 1,
+        row_gap: 2,
+    },
+    // boxed nodes
+    Scale {
+        node_w: 7,
+        node_h: 3,
+        col_gap: 2,
+        row_gap: 3,
+    },
+];
+
+#[derive(Clone, Copy, Default)]
+struct Cell {
+    mask: u8,
+    ch: Option<char>, // node text and arrows, drawn over the lines
+    style: Style,
+}
+
+// the whole graph drawn into a grid of characters
+struct Canvas {
+    cells: Vec<Vec<Cell>>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            cells: vec![vec![Cell::default(); width]; height],
+        }
+    }
+
+    fn join(&mut self, (x0, y0): (usize, usize), (x1, y1): (usize, usize), style: Style) {
+        let (a, b) = if x0 == x1 { (DOWN, UP) } else { (RIGHT, LEFT) };
+        self.mark(x0, y0, a, style);
+        self.mark(x1, y1, b, style);
+    }
+
+    fn vertical(&mut self, x: usize, from: usize, to: usize, style: Style) {
+        for y in from.min(to)..from.max(to) {
+            self.join((x, y), (x, y + 1), style);
+        }
+    }
+
+    fn horizontal(&mut self, y: usize, from: usize, to: usize, style: Style) {
+        for x in from.min(to)..from.max(to) {
+            self.join((x, y), (x + 1, y), style);
+        }
+    }
+
+    // line leaving a cell in the given direction, also towards a node
+    fn mark(&mut self, x: usize, y: usize, dir: u8, style: Style) {
+        if let Some(cell) = self.cells.get_mut(y).and_then(|row| row.get_mut(x)) {
+            cell.mask |= dir;
+            // lines on the player's path win over the others
+            if cell.style == Style::default() || style.fg == Some(Color::Yellow) {
+                cell.style = style;
+            }
+        }
+    }
+
+    fn put(&mut self, x: usize, y: usize, ch: char, style: Style) {
+        if let Some(cell) = self.cells.get_mut(y).and_then(|row| row.get_mut(x)) {
+            cell.ch = Some(ch);
+            cell.style = style;
+        }
+    }
+
+    fn text(&mut self, x: usize, y: usize, text: &str, style: Style) {
+        for (i, ch) in text.chars().enumerate() {
+            self.put(x + i, y, ch, style);
+        }
+    }
+
+    fn lines(&self, x: usize, y: usize, width: usize, height: usize) -> Vec<Line<'static>> {
+        self.cells
+            .iter()
+            .skip(y)
+            .take(height)
+            .map(|row| {
+                let spans = row
+                    .iter()
+                    .skip(x)
+                    .take(width)
+                    .map(|cell| {
+                        let ch = match cell.ch {
+                            Some(ch) => ch,
+                            None if cell.mask != 0 => glyph(cell.mask),
+                            None => ' ',
+                        };
+                        Span::styled(ch.to_string(), cell.style)
+                    })
+                    .collect::<Vec<_>>();
+                Line::from(spans)
+            })
+            .collect()
+    }
+}
+
+// layer of each screen by its distance from the start, screens the start can't
+// reach are laid out below
+fn layer_of(graph: &GameGraph, start: usize) -> HashMap<usize, usize> {
+    let mut screens = graph.graph.keys().copied().collect::<Vec<_>>();
+    screens.sort_unstable();
+    let mut layer_of: HashMap<usize, usize> = HashMap::new();
+    let mut roots = vec![start];
+    roots.extend(screens.iter().copied());
+    for root in roots {
+        if layer_of.contains_key(&root) || !graph.graph.contains_key(&root) {
+            continue;
+        }
+        let first = layer_of.values().max().map(|l| l + 1).unwrap_or(0);
+        layer_of.insert(root, first);
+        let mut queue = VecDeque::from([root]);
+        while let Some(screen_no) = queue.pop_front() {
+            for next in &graph.graph[&screen_no] {
+                if graph.graph.contains_key(next) && !layer_of.contains_key(next) {
+                    layer_of.insert(*next, layer_of[&screen_no] + 1);
+                    queue.push_back(*next);
+                }
+            }
+        }
+    }
+    layer_of
+}
+
+// place in a layer: a screen, or a point where a longer edge passes through the layer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Slot {
+    Screen(usize),
+    Dummy(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Flow {
+    Down,   // to a later layer
+    Up,     // back to an earlier layer
+    Across, // to a screen of the same layer
+}
+
+// edge of the story graph laid out over consecutive layers
+#[derive(Debug, Clone, PartialEq)]
+struct Route {
+    from: usize,
+    to: usize,
+    flow: Flow,
+    slots: Vec<Slot>, // from the top layer down, with a dummy in every layer in between
+}
+
+// screens and the edges passing through them, layer by layer
+struct Layered {
+    layers: Vec<Vec<Slot>>,
+    routes: Vec<Route>,
+}
+
+fn layout(graph: &GameGraph, start: usize) -> Layered {
+    let layer_of = layer_of(graph, start);
+    let mut screens = graph.graph.keys().copied().collect::<Vec<_>>();
+    screens.sort_unstable();
+    let mut layers = vec![Vec::new(); layer_of.values().max().map(|l| l + 1).unwrap_or(0)];
+    for &screen_no in &screens {
+        layers[layer_of[&screen_no]].push(Slot::Screen(screen_no));
+    }
+
+    let mut routes = Vec::new();
+    let mut dummies = 0;
+    for &from in &screens {
+        let mut targets = graph.graph[&from]
+            .iter()
+            .copied()
+            .filter(|to| *to != from && layer_of.contains_key(to))
+            .collect::<Vec<_>>();
+        targets.sort_unstable();
+        targets.dedup();
+        for to in targets {
+            let (flow, top, bottom) = match layer_of[&to].cmp(&layer_of[&from]) {
+                Ordering::Greater => (Flow::Down, from, to),
+                Ordering::Less => (Flow::Up, to, from),
+                Ordering::Equal => (Flow::Across, from, to),
+            };
+            let mut slots = vec![Slot::Screen(top)];
+            let between = (layer_of[&top] + 1)..layer_of[&bottom].max(layer_of[&top] + 1);
+            for layer in &mut layers[between] {
+                let dummy = Slot::Dummy(dummies);
+                dummies += 1;
+                layer.push(dummy);
+                slots.push(dummy);
+            }
+            slots.push(Slot::Screen(bottom));
+            routes.push(Route {
+                from,
+                to,
+                flow,
+                slots,
+            });
+        }
+    }
+    order_layers(&mut layers, &routes);
+    Layered { layers, routes }
+}
+
+// orders each layer by the mean position of its neighbours in the layer above
+// (barycenter heuristic) to cut down crossings
+fn order_layers(layers: &mut [Vec<Slot>], routes: &[Route]) {
+    let mut above: HashMap<Slot, Vec<Slot>> = HashMap::new();
+    for route in routes.iter().filter(|r| r.flow != Flow::Across) {
+        for pair in route.slots.windows(2) {
+            above.entry(pair[1]).or_default().push(pair[0]);
+        }
+    }
+    for _ in 0..2 {
+        for l in 1..layers.len() {
+            let position = layers[l - 1]
+                .iter()
+                .enumerate()
+                .map(|(i, slot)| (*slot, i as f32))
+                .collect::<HashMap<_, _>>();
+            let mut keyed = layers[l]
+                .iter()
+                .enumerate()
+                .map(|(i, slot)| {
+                    let parents = above
+                        .get(slot)
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|parent| position.get(parent))
+                        .collect::<Vec<_>>();
+                    let key = if parents.is_empty() {
+                        i as f32
+                    } else {
+                        parents.iter().copied().sum::<f32>() / parents.len() as f32
+                    };
+                    (key, *slot)
+                })
+                .collect::<Vec<_>>();
+            keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+            layers[l] = keyed.into_iter().map(|(_, slot)| slot).collect();
+        }
+    }
+}
+
+// columns where edges meet a slot, from its left edge; the ones below a layer and the
+// ones above the next layer never line up, so legs of different slots never share a
+// column of a gap
+fn ports(scale: &Scale) -> Ports {
+    let center = scale.node_w / 2;
+    Ports {
+        leave: center,
+        arrive: center + 1,
+        back_leave: center - 1,
+        back_arrive: center + 2,
+    }
+}
+
+struct Ports {
+    leave: usize,       // bottom, towards a later layer
+    arrive: usize,      // top, from an earlier layer
+    back_leave: usize,  // top, back to an earlier layer
+    back_arrive: usize, // bottom, back from a later layer or across from the same one
+}
+
+// part of a route in the gap below a layer
+struct Leg {
+    route: usize,
+    gap: usize,       // layer above the gap
+    top: usize,       // column at the layer above
+    bottom: usize,    // column at the layer below, at the layer above for Across
+    up_arrow: bool,   // ▲ into the slot above
+    down_arrow: bool, // ▼ into the slot below
+    track: usize,     // row of the horizontal part, counted from the top of the gap
+}
+
+// rows of the layers and the legs in the gaps between them at the current zoom
+struct Geometry {
+    layer_y: Vec<usize>, // top row of each layer
+    gap_h: Vec<usize>,   // height of the gap below each layer
+    legs: Vec<Leg>,
+}
+
+// no two legs of a gap share a track where they overlap
+fn assign_tracks(legs: &mut [Leg]) -> usize {
+    let mut order = (0..legs.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&i| {
+        (
+            legs[i].top.min(legs[i].bottom),
+            legs[i].top.max(legs[i].bottom),
+        )
+    });
+    let mut ends: Vec<usize> = Vec::new();
+    for i in order {
+        let (left, right) = (
+            legs[i].top.min(legs[i].bottom),
+            legs[i].top.max(legs[i].bottom),
+        );
+        match ends.iter().position(|end| *end < left) {
+            Some(track) => {
+                ends[track] = right;
+                legs[i].track = track;
+            }
+            None => {
+                legs[i].track = ends.len();
+                ends.push(right);
+            }
+        }
+    }
+    ends.len()
+}
+
+// same colour buckets as the visit counts always had
+fn visit_style(count: usize) -> Style {
+    let color = match count {
+        0 => Color::DarkGray,
+        1..=2 => Color::Blue,
+        3..=5 => Color::Cyan,
+        6..=10 => Color::Green,
+        11..=20 => Color::Yellow,
+        21..=50 => Color::Magenta,
+        _ => Color::Red,
+    };
+    Style::default().fg(color)
+}
+
+// layered node-and-edge view of the story shown after an ending
+pub struct GraphView {
+    layers: Vec<Vec<Slot>>,
+    routes: Vec<Route>,
+    order: Vec<usize>, // screens in layer order, for moving the focus
+    focus: usize,      // index into `order`
+    scroll_x: usize,
+    scroll_y: usize,
+    zoom: usize, // index into SCALES
+    viewport: (usize, usize),
+}
+
+impl GraphView {
+    pub(crate) fn new(graph: &GameGraph, start: usize) -> Self {
+        let Layered { layers, routes } = layout(graph, start);
+        let order = layers
+            .iter()
+            .flatten()
+            .filter_map(|slot| match slot {
+                Slot::Screen(screen_no) => Some(*screen_no),
+                Slot::Dummy(_) => None,
+            })
+            .collect();
+        Self {
+            layers,
+            routes,
+            order,
+            focus: 0,
+            scroll_x: 0,
+            scroll_y: 0,
+            zoom: 1,
+            viewport: (80, 20),
+        }
+    }
+
+    // focus the given screen, usually the ending just reached
+    pub(crate) fn focus_on(&mut self, screen_no: usize) {
+        if let Some(i) = self.order.iter().position(|s| *s == screen_no) {
+            self.focus = i;
+            self.scroll_to_focus();
+        }
+    }
+
+    fn scale(&self) -> &Scale {
+        &SCALES[self.zoom]
+    }
+
+    // layer and index of a slot
+    fn place(&self, slot: Slot) -> Option<(usize, usize)> {
+        self.layers
+            .iter()
+            .enumerate()
+            .find_map(|(l, layer)| Some((l, layer.iter().position(|s| *s == slot)?)))
+    }
+
+    fn slot_x(&self, i: usize) -> usize {
+        1 + i * (self.scale().node_w + self.scale().col_gap)
+    }
+
+    // legs of every route and the gap heights they need
+    fn geometry(&self) -> Geometry {
+        let scale = self.scale();
+        let ports = ports(scale);
+        let places = self
+            .layers
+            .iter()
+            .enumerate()
+            .flat_map(|(l, layer)| layer.iter().enumerate().map(move |(i, s)| (*s, (l, i))))
+            .collect::<HashMap<_, _>>();
+
+        let mut legs = Vec::new();
+        for (r, route) in self.routes.iter().enumerate() {
+            if route.flow == Flow::Across {
+                let (l, i) = places[&route.slots[0]];
+                let (_, j) = places[&route.slots[1]];
+                legs.push(Leg {
+                    route: r,
+                    gap: l,
+                    top: self.slot_x(i) + ports.leave,
+                    bottom: self.slot_x(j) + ports.back_arrive,
+                    up_arrow: true,
+                    down_arrow: false,
+                    track: 0,
+                });
+                continue;
+            }
+            let last = route.slots.len() - 2;
+            for (k, pair) in route.slots.windows(2).enumerate() {
+                let (l, i) = places[&pair[0]];
+                let (_, j) = places[&pair[1]];
+                let head = route.flow == Flow::Up && k == 0;
+                let top = if head { ports.back_arrive } else { ports.leave };
+                let bottom = match (pair[1], route.flow) {
+                    (Slot::Screen(_), Flow::Up) => ports.back_leave,
+                    _ => ports.arrive,
+                };
+                legs.push(Leg {
+                    route: r,
+                    gap: l,
+                    top: self.slot_x(i) + top,
+                    bottom: self.slot_x(j) + bottom,
+                    up_arrow: head,
+                    down_arrow: route.flow == Flow::Down && k == last,
+                    track: 0,
+                });
+            }
+        }
+
+        let mut layer_y = Vec::new();
+        let mut gap_h = Vec::new();
+        let mut y = 1;
+        for l in 0..self.layers.len() {
+            let (mut in_gap, rest): (Vec<_>, Vec<_>) =
+                legs.into_iter().partition(|leg| leg.gap == l);
+            let tracks = assign_tracks(&mut in_gap);
+            legs = rest.into_iter().chain(in_gap).collect();
+            layer_y.push(y);
+            // a row for the ▲ and the tails, the tracks, then a row for the ▼
+            let h = if tracks == 0 {
+                scale.row_gap
+            } else {
+                scale.row_gap.max(tracks + 2)
+            };
+            gap_h.push(h);
+            y += scale.node_h + h;
+        }
+        Geometry {
+            layer_y,
+            gap_h,
+            legs,
+        }
+    }
+
+    // top left corner of a screen's node on the canvas
+    fn node_position(&self, screen_no: usize) -> Option<(usize, usize)> {
+        let (l, i) = self.place(Slot::Screen(screen_no))?;
+        Some((self.slot_x(i), self.geometry().layer_y[l]))
+    }
+
+    fn canvas_size(&self, geometry: &Geometry) -> (usize, usize) {
+        let scale = self.scale();
+        let widest = self.layers.iter().map(Vec::len).max().unwrap_or(0);
+        let height = match (geometry.layer_y.last(), geometry.gap_h.last()) {
+            (Some(y), Some(h)) => y + scale.node_h + h + 1,
+            _ => 2,
+        };
+        (2 + widest * (scale.node_w + scale.col_gap), height)
+    }
+
+    fn scroll_to_focus(&mut self) {
+        let Some((x, y)) = self
+            .order
+            .get(self.focus)
+            .and_then(|s| self.node_position(*s))
+        else {
+            return;
+        };
+        let (w, h) = self.viewport;
+        let (node_w, node_h) = (self.scale().node_w, self.scale().node_h);
+        if x < self.scroll_x || x + node_w > self.scroll_x + w {
+            self.scroll_x = x.saturating_sub(w / 2);
+        }
+        if y < self.scroll_y || y + node_h > self.scroll_y + h {
+            self.scroll_y = y.saturating_sub(h / 2);
+        }
+    }
+
+    fn draw(&self, graph: &GameGraph) -> Canvas {
+        let geometry = self.geometry();
+        let (width, height) = self.canvas_size(&geometry);
+        let mut canvas = Canvas::new(width, height);
+        let scale = self.scale();
+        let ports = ports(scale);
+        let path_edges = graph
+            .path
+            .windows(2)
+            .map(|w| (w[0], w[1]))
+            .collect::<HashSet<_>>();
+        let focused = self.order.get(self.focus).copied();
+        let route_style = |route: &Route| {
+            if path_edges.contains(&(route.from, route.to)) {
+                Style::default().fg(Color::Yellow)
+            } else if focused == Some(route.from) || focused == Some(route.to) {
+                Style::default().fg(Color::White)
+            } else {
+                Style::default().fg(Color::DarkGray)
+            }
+        };
+
+        // every leg goes down from the layer above to its own track, across, then
+        // on to the layer below, or back up for an edge within a layer
+        for leg in &geometry.legs {
+            let style = route_style(&self.routes[leg.route]);
+            let top = geometry.layer_y[leg.gap] + scale.node_h;
+            let track = top + 1 + leg.track;
+            let bottom = top + geometry.gap_h[leg.gap] - 1;
+            canvas.vertical(leg.top, top, track, style);
+            canvas.horizontal(track, leg.top, leg.bottom, style);
+            if self.routes[leg.route].flow == Flow::Across {
+                canvas.vertical(leg.bottom, track, top, style);
+                canvas.mark(leg.top, top, UP, style);
+                canvas.put(leg.bottom, top, '▲', style);
+                continue;
+            }
+            canvas.vertical(leg.bottom, track, bottom, style);
+            if leg.up_arrow {
+                canvas.put(leg.top, top, '▲', style);
+            } else {
+                canvas.mark(leg.top, top, UP, style);
+            }
+            if leg.down_arrow {
+                canvas.put(leg.bottom, bottom, '▼', style);
+            } else {
+                canvas.mark(leg.bottom, bottom, DOWN, style);
+            }
+        }
+
+        // longer edges step through the layers they cross where the dummies are
+        for route in &self.routes {
+            let style = route_style(route);
+            for slot in &route.slots[1..route.slots.len() - 1] {
+                let Some((l, i)) = self.place(*slot) else {
+                    continue;
+                };
+                let (x, y) = (self.slot_x(i), geometry.layer_y[l]);
+                let mid = y + scale.node_h / 2;
+                canvas.vertical(x + ports.arrive, y - 1, mid, style);
+                canvas.horizontal(mid, x + ports.arrive, x + ports.leave, style);
+                canvas.vertical(x + ports.leave, mid, y + scale.node_h, style);
+            }
+        }
+
+        for &screen_no in &self.order {
+            let Some((l, i)) = self.place(Slot::Screen(screen_no)) else {
+                continue;
+            };
+            let (x, y) = (self.slot_x(i), geometry.layer_y[l]);
+            let visits = graph.visited.get(&screen_no).copied().unwrap_or(0);
+            let mut style = visit_style(visits);
+            if graph.path.contains(&screen_no) {
+                style = style.add_modifier(Modifier::BOLD);
+            }
+            if focused == Some(screen_no) {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            let ending = graph.endings.contains(&screen_no);
+            let label = format!("{:^width$}", screen_no, width = scale.node_w - 2);
+            if scale.node_h == 1 {
+                let (open, close) = if ending { ('(', ')') } else { ('[', ']') };
+                canvas.text(x, y, &format!("{}{}{}", open, label, close), style);
+                continue;
+            }
+            let [tl, h, tr, v, bl, br] = if ending {
+                ['╔', '═', '╗', '║', '╚', '╝']
+            } else {
+                ['┌', '─', '┐', '│', '└', '┘']
+            };
+            let bar = h.to_string().repeat(scale.node_w - 2);
+            canvas.text(x, y, &format!("{}{}{}", tl, bar, tr), style);
+            canvas.text(x, y + 1, &format!("{}{}{}", v, label, v), style);
+            canvas.text(x, y + 2, &format!("{}{}{}", bl, bar, br), style);
+        }
+        canvas
+    }
+
+    // incoming and outgoing choices of the focused screen
+    fn details(&self, graph: &GameGraph) -> Vec<Line<'static>> {
+        let Some(&screen_no) = self.order.get(self.focus) else {
+            return Vec::new();
+        };
+        let mut header = format!("Screen {}", screen_no);
+        if let Some(location) = graph.locations.get(&screen_no) {
+            header.push_str(&format!(" - {}", location));
+        }
+        if graph.endings.contains(&screen_no) {
+            header.push_str(" - ending");
+        }
+        let visits = graph.visited.get(&screen_no).copied().unwrap_or(0);
+        header.push_str(&format!(" - visited {}x", visits));
+
+        let mut lines = vec![Line::styled(
+            header,
+            Style::default().add_modifier(Modifier::BOLD),
+        )];
+        let mut sources = graph
+            .choices
+            .iter()
+            .flat_map(|(from, choices)| {
+                choices
+                    .iter()
+                    .filter(|c| c.targets().contains(&screen_no))
+                    .map(move |c| (*from, c.text.trim().to_string()))
+            })
+            .collect::<Vec<_>>();
+        sources.sort();
+        for (from, text) in sources {
+            lines.push(Line::from(format!("  {:>3} -> \"{}\"", from, text)));
+        }
+        for choice in graph.choices.get(&screen_no).into_iter().flatten() {
+            let targets = choice
+                .targets()
+                .iter()
+                .map(|t| t.to_string())
+                .collect::<Vec<_>>()
+                .join("/");
+            lines.push(Line::from(format!(
+                "  \"{}\" -> {}",
+                choice.text.trim(),
+                targets
+            )));
+        }
+        lines
+    }
+
+    pub fn render(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+        graph: &GameGraph,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let canvas = self.draw(graph);
+        let details = self.details(graph);
+        terminal.draw(|f| {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Min(0),
+                    Constraint::Length(details.len().min(8) as u16 + 2),
+                ])
+                .split(f.area());
+
+            let block = Block::default()
+                .title("Game Graph - arrows pan, Tab focus, z zoom, Enter back")
+                .borders(Borders::ALL);
+            let inner = block.inner(chunks[0]);
+            self.viewport = (inner.width as usize, inner.height as usize);
+            let lines = canvas.lines(
+                self.scroll_x,
+                self.scroll_y,
+                inner.width as usize,
+                inner.height as usize,
+            );
+            f.render_widget(Paragraph::new(lines).block(block), chunks[0]);
+
+            let panel = Paragraph::new(details).block(
+                Block::default()
+                    .title("Focus")
+                    .border_style(Style::default().fg(Color::Cyan))
+                    .borders(Borders::ALL),
+            );
+            f.render_widget(panel, chunks[1]);
+        })?;
+        Ok(())
+    }
+
+    pub fn key_event(&mut self, key_code: KeyCode) -> Option<GameEvent> {
+        let (width, height) = self.canvas_size(&self.geometry());
+        let (vw, vh) = self.viewport;
+        match key_code {
+            KeyCode::Enter | KeyCode::Esc => return Some(GameEvent::Exit),
+            KeyCode::Left => self.scroll_x = self.scroll_x.saturating_sub(4),
+            KeyCode::Right => self.scroll_x = (self.scroll_x + 4).min(width.saturating_sub(vw)),
+            KeyCode::Up => self.scroll_y = self.scroll_y.saturating_sub(2),
+            KeyCode::Down => self.scroll_y = (self.scroll_y + 2).min(height.saturating_sub(vh)),
+            KeyCode::Tab if !self.order.is_empty() => {
+                self.focus = (self.focus + 1) % self.order.len();
+                self.scroll_to_focus();
+            }
+            KeyCode::BackTab if !self.order.is_empty() => {
+                self.focus = (self.focus + self.order.len() - 1) % self.order.len();
+                self.scroll_to_focus();
+            }
+            KeyCode::Char('z') => {
+                self.zoom = (self.zoom + 1) % SCALES.len();
+                self.scroll_to_focus();
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layers_follow_the_distance_from_the_start() {
+        let graph =
+            GameGraph::from_targets(&[(0, &[1, 2]), (1, &[3]), (2, &[3]), (3, &[]), (4, &[0])]);
+        let layer_of = layer_of(&graph, 0);
+        assert_eq!(
+            [0, 1, 2, 3, 4].map(|s| layer_of[&s]),
+            [0, 1, 1, 2, 3],
+            "screens the start can't reach go below"
+        );
+    }
+
+    #[test]
+    fn long_edges_pass_a_dummy_in_every_layer_they_cross() {
+        let graph = GameGraph::from_targets(&[(0, &[1]), (1, &[2]), (2, &[3]), (3, &[0])]);
+        let Layered { layers, routes } = layout(&graph, 0);
+        let back = routes.iter().find(|r| r.from == 3).unwrap();
+        assert_eq!(back.flow, Flow::Up);
+        assert!(matches!(
+            back.slots[..],
+            [
+                Slot::Screen(0),
+                Slot::Dummy(_),
+                Slot::Dummy(_),
+                Slot::Screen(3)
+            ]
+        ));
+        assert!(layers[1].contains(&back.slots[1]));
+        assert!(layers[2].contains(&back.slots[2]));
+    }
+
+    #[test]
+    fn layers_are_ordered_by_their_parents() {
+        let graph =
+            GameGraph::from_targets(&[(0, &[1, 2]), (1, &[4]), (2, &[3]), (3, &[]), (4, &[])]);
+        let Layered { layers, .. } = layout(&graph, 0);
+        assert_eq!(layers[1], [Slot::Screen(1), Slot::Screen(2)]);
+        assert_eq!(layers[2], [Slot::Screen(4), Slot::Screen(3)]);
+    }
+
+    #[test]
+    fn edges_go_around_the_nodes() {
+        let graph = GameGraph::from_targets(&[
+            (0, &[1, 2]),
+            (1, &[3, 2]),
+            (2, &[4]),
+            (3, &[4, 0]),
+            (4, &[1]),
+            (5, &[2]),
+        ]);
+        let mut view = GraphView::new(&graph, 0);
+        for zoom in 0..SCALES.len() {
+            view.zoom = zoom;
+            let canvas = view.draw(&graph);
+            let scale = view.scale();
+            for &screen_no in &view.order {
+                let (x, y) = view.node_position(screen_no).unwrap();
+                for row in &canvas.cells[y..y + scale.node_h] {
+                    assert!(row[x..x + scale.node_w].iter().all(|c| c.mask == 0));
+                }
+            }
+        }
+    }
+}
@@ -3,34 +3,37 @@ use std::io;
 use crossterm::event::KeyCode;
 use ratatui::{
     Terminal,
-    layout::{Alignment, Constraint, Direction, Layout},
     prelude::CrosstermBackend,
     style::{Color, Modifier, Style},
-    widgets::{Block, Borders, List, ListState, Paragraph},
+    widgets::{Block, Borders, List, ListState},
 };
 
-use crate::engine::{config, fs};
+use crate::engine::{config, fs, gfx::Painter};
 
 pub struct IntroScreen {
-    pub(crate) width: usize,
-    pub(crate) height: usize,
     internal_item_selected: usize,
     menu_item_selected: Option<usize>,
     intro_image: Vec<Vec<(u8, u8, u8)>>,
+    painter: Painter,
     list_state: ListState,
 }
 
 pub const NEW_GAME: usize = 0;
 pub const CONTINUE: usize = 1;
-pub const SAVE: usize = 2;
-pub const ACHIEVEMENTS: usize = 3;
-pub const CREDITS: usize = 4;
-pub const EXIT: usize = 5;
+pub const LOAD: usize = 2;
+pub const SAVE: usize = 3;
+pub const INVESTIGATOR: usize = 4;
+pub const ACHIEVEMENTS: usize = 5;
+#[allow(dead_code)] // credits screen is not there yet
+pub const CREDITS: usize = 6;
+pub const EXIT: usize = 7;
 
-const MENU_ITEMS: [&'static str; 6] = [
+const MENU_ITEMS: [&str; 8] = [
     "New Game",
-    "Continue Saved...",
-    "Save",
+    "Continue",
+    "Load Game...",
+    "Save Game...",
+    "Investigator...",
     "Achievements",
     "Credits",
     "Exit",
@@ -45,11 +48,10 @@ impl IntroScreen {
         let mut list_state = ListState::default();
         list_state.select(Some(0));
         Ok(Self {
-            width,
-            height,
             internal_item_selected: 0,
             menu_item_selected: None,
             intro_image,
+            painter: Painter::new(config),
             list_state,
         })
     }
@@ -62,15 +64,7 @@ impl IntroScreen {
             let area = f.area();
 
             // Draw intro image across full terminal
-            for (y, row) in self.intro_image.iter().enumerate() {
-                for (x, (r, g, b)) in row.iter().enumerate() {
-                    if y < area.height as usize && x < area.width as usize {
-                        if let Some(cell) = f.buffer_mut().cell_mut((x as u16, y as u16)) {
-                            cell.set_bg(Color::Rgb(*r, *g, *b));
-                        }
-                    }
-                }
-            }
+            self.painter.draw(f.buffer_mut(), area, &self.intro_image);
 
             // Menu overlay in bottom half
             let menu_area = ratatui::layout::Rect {
@@ -90,6 +84,8 @@ impl IntroScreen {
                 )
                 .highlight_symbol("> ");
             f.render_stateful_widget(menu, menu_area, &mut self.list_state.clone());
+            self.painter
+                .restore(f.buffer_mut(), area, &self.intro_image);
         })?;
 
         Ok(())
@@ -0,0 +1,186 @@
+use std::io;
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    Terminal,
+    layout::{Constraint, Direction, Layout},
+    prelude::CrosstermBackend,
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, List, ListState, Paragraph},
+};
+
+use crate::engine::{
+    config, fs,
+    gfx::Painter,
+    investigator::{BASE_SKILLS, CHARACTERISTICS, Investigator},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InvestigatorEvent {
+    Accept(Investigator),
+    Cancel,
+}
+
+// investigator creation: the pre-generated narrator or point-buy
+pub struct InvestigatorScreen {
+    investigator: Investigator,
+    image: Vec<Vec<(u8, u8, u8)>>,
+    painter: Painter,
+    list_state: ListState,
+}
+
+const STEP: i32 = 5;
+
+impl InvestigatorScreen {
+    pub fn new(
+        width: usize,
+        height: usize,
+        config: &config::Config,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let image = fs::load_intro_screen_image(width as u16, height as u16, config)?;
+        let mut list_state = ListState::default();
+        list_state.select(Some(0));
+        Ok(Self {
+            investigator: Investigator::default(),
+            image,
+            painter: Painter::new(config),
+            list_state,
+        })
+    }
+
+    // start editing from the currently chosen investigator
+    pub fn open(&mut self, investigator: &Investigator) {
+        self.investigator = investigator.clone();
+        self.list_state.select(Some(0));
+    }
+
+    fn rows() -> usize {
+        CHARACTERISTICS.len() + BASE_SKILLS.len()
+    }
+
+    // the pre-generated sheet is fine as it is, point-buy must not overspend
+    fn is_valid(&self) -> bool {
+        self.investigator == Investigator::thurston()
+            || (self.investigator.characteristic_points_left() >= 0
+                && self.investigator.skill_points_left() >= 0)
+    }
+
+    fn adjust(&mut self, delta: i32) {
+        let Some(selected) = self.list_state.selected() else {
+            return;
+        };
+        if let Some(characteristic) = CHARACTERISTICS.get(selected) {
+            self.investigator
+                .adjust_characteristic(characteristic, delta);
+        } else if let Some((skill, _)) = BASE_SKILLS.get(selected - CHARACTERISTICS.len()) {
+            self.investigator.adjust_skill(skill, delta);
+        }
+    }
+
+    pub fn render(
+        &self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        terminal.draw(|f| {
+            let area = f.area();
+
+            self.painter.draw(f.buffer_mut(), area, &self.image);
+
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(6), Constraint::Min(0)].as_ref())
+                .split(area);
+
+            let inv = &self.investigator;
+            let header = Paragraph::new(vec![
+                Line::from(format!("{} - {}", inv.name, inv.occupation)),
+                Line::from(format!(
+                    "HP {}  Sanity {}  Luck {}",
+                    inv.max_hp, inv.sanity, inv.luck
+                )),
+                Line::styled(
+                    format!(
+                        "Characteristic points left: {}   Skill points left: {}",
+                        inv.characteristic_points_left(),
+                        inv.skill_points_left()
+                    ),
+                    if self.is_valid() {
+                        Style::default().fg(Color::White)
+                    } else {
+                        Style::default().fg(Color::Red)
+                    },
+                ),
+                Line::styled(
+                    "Left/Right adjust  T pre-generated Thurston  B point-buy  Enter accept  Esc cancel",
+                    Style::default().fg(Color::Gray),
+                ),
+            ])
+            .block(Block::default().borders(Borders::ALL).title("Investigator"));
+            f.render_widget(header, chunks[0]);
+
+            let items = CHARACTERISTICS
+                .iter()
+                .map(|c| Line::from(format!("{:<16} {:>3}", c, inv.characteristic(c))))
+                .chain(BASE_SKILLS.iter().map(|(skill, _)| {
+                    Line::from(format!("{:<16} {:>3}%", skill, inv.skill(skill)))
+                }))
+                .collect::<Vec<_>>();
+            let list = List::new(items)
+                .block(Block::default().borders(Borders::ALL))
+                .style(Style::default().fg(Color::White))
+                .highlight_style(
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .highlight_symbol("> ");
+            f.render_stateful_widget(list, chunks[1], &mut self.list_state.clone());
+            self.painter.restore(f.buffer_mut(), area, &self.image);
+        })?;
+
+        Ok(())
+    }
+
+    pub fn key_event(&mut self, key_code: KeyCode) -> Option<InvestigatorEvent> {
+        match key_code {
+            KeyCode::Up => {
+                self.list_state.select_previous();
+                None
+            }
+            KeyCode::Down => {
+                if let Some(selected) = self.list_state.selected()
+                    && selected + 1 < Self::rows()
+                {
+                    self.list_state.select(Some(selected + 1));
+                }
+                None
+            }
+            KeyCode::Left => {
+                self.adjust(-STEP);
+                None
+            }
+            KeyCode::Right => {
+                self.adjust(STEP);
+                None
+            }
+            KeyCode::Char('t') | KeyCode::Char('T') => {
+                self.investigator = Investigator::thurston();
+                None
+            }
+            KeyCode::Char('b') | KeyCode::Char('B') => {
+                self.investigator = Investigator::point_buy();
+                None
+            }
+            KeyCode::Enter => {
+                if self.is_valid() {
+                    Some(InvestigatorEvent::Accept(self.investigator.clone()))
+                } else {
+                    None
+                }
+            }
+            KeyCode::Esc => Some(InvestigatorEvent::Cancel),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use std::{io, time::Duration};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    Terminal,
+    layout::{Constraint, Direction, Layout},
+    prelude::CrosstermBackend,
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, List, ListState, Paragraph},
+};
+
+use crate::screens::graph::GameGraph;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JourneyEvent {
+    Exit,
+    Graph,  // switch over to the story graph
+    Export, // write the timeline out as json
+}
+
+// how many entries PageUp/PageDown skip
+const PAGE: usize = 10;
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+// scrollable log of the player's way through the story, shown after an ending
+pub struct JourneyScreen {
+    list_state: ListState,
+    status: Option<String>,
+}
+
+impl JourneyScreen {
+    pub fn new() -> Self {
+        Self {
+            list_state: ListState::default(),
+            status: None,
+        }
+    }
+
+    // start at the top of a freshly finished journey
+    pub fn open(&mut self) {
+        self.list_state.select(Some(0));
+        self.status = None;
+    }
+
+    pub fn set_status(&mut self, status: String) {
+        self.status = Some(status);
+    }
+
+    fn entries(graph: &GameGraph) -> Vec<Line<'static>> {
+        let mut location = None;
+        graph
+            .timeline
+            .steps
+            .iter()
+            .zip(graph.timeline.durations())
+            .zip(graph.timeline.offsets())
+            .map(|((step, spent), since_start)| {
+                // locations are only announced when they change
+                if let Some(announced) = graph.locations.get(&step.screen_no) {
+                    location = Some(announced.clone());
+                }
+                let choice = match &step.choice {
+                    Some(choice) => format!("<- \"{}\"", choice),
+                    None => "start".to_string(),
+                };
+                let mut line = format!(
+                    "+{}  screen {:>3}  {:<14}  {}  ({})",
+                    format_duration(since_start),
+                    step.screen_no,
+                    location.as_deref().unwrap_or(""),
+                    choice,
+                    format_duration(spent)
+                );
+                if graph.endings.contains(&step.screen_no) {
+                    line.push_str("  THE END");
+                }
+                Line::from(line)
+            })
+            .collect()
+    }
+
+    fn stats(graph: &GameGraph) -> Vec<Line<'static>> {
+        let total = graph.timeline.durations().iter().sum::<Duration>();
+        let mut times = graph
+            .timeline
+            .time_per_screen()
+            .into_iter()
+            .collect::<Vec<_>>();
+        times.sort_by_key(|(_, spent)| std::cmp::Reverse(*spent));
+        let longest = times
+            .iter()
+            .take(3)
+            .map(|(screen_no, spent)| format!("{} ({})", screen_no, format_duration(*spent)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        vec![
+            Line::from(format!(
+                "{} steps, {} distinct screens, {} in total",
+                graph.timeline.steps.len(),
+                times.len(),
+                format_duration(total)
+            )),
+            Line::from(format!("Longest on screens: {}", longest)),
+        ]
+    }
+
+    pub fn render(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+        graph: &GameGraph,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let entries = Self::entries(graph);
+        let mut stats = Self::stats(graph);
+        if let Some(status) = &self.status {
+            stats.push(Line::styled(
+                status.clone(),
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+        terminal.draw(|f| {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Min(0),
+                    Constraint::Length(stats.len() as u16 + 2),
+                ])
+                .split(f.area());
+
+            let list = List::new(entries)
+                .block(
+                    Block::default()
+                        .title("Journey - g graph, e export json, Enter back")
+                        .borders(Borders::ALL),
+                )
+                .style(Style::default().fg(Color::White))
+                .highlight_style(
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .highlight_symbol("> ");
+            f.render_stateful_widget(list, chunks[0], &mut self.list_state);
+
+            let panel = Paragraph::new(stats).block(
+                Block::default()
+                    .title("Statistics")
+                    .border_style(Style::default().fg(Color::Cyan))
+                    .borders(Borders::ALL),
+            );
+            f.render_widget(panel, chunks[1]);
+        })?;
+        Ok(())
+    }
+
+    pub fn key_event(&mut self, key_code: KeyCode, len: usize) -> Option<JourneyEvent> {
+        let selected = self.list_state.selected().unwrap_or(0);
+        let last = len.saturating_sub(1);
+        match key_code {
+            KeyCode::Enter | KeyCode::Esc => return Some(JourneyEvent::Exit),
+            KeyCode::Char('g') => return Some(JourneyEvent::Graph),
+            KeyCode::Char('e') => return Some(JourneyEvent::Export),
+            KeyCode::Up => self.list_state.select(Some(selected.saturating_sub(1))),
+            KeyCode::Down => self.list_state.select(Some((selected + 1).min(last))),
+            KeyCode::PageUp => self.list_state.select(Some(selected.saturating_sub(PAGE))),
+            KeyCode::PageDown => self.list_state.select(Some((selected + PAGE).min(last))),
+            KeyCode::Home => self.list_state.select(Some(0)),
+            KeyCode::End => self.list_state.select(Some(last)),
+            _ => {}
+        }
+        None
+    }
+}
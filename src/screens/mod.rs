@@ -1,4 +1,8 @@
 pub(crate) mod achievements;
 pub(crate) mod graph;
+pub(crate) mod graph_view;
 pub(crate) mod intro_screen;
+pub(crate) mod investigator;
+pub(crate) mod journey;
 pub(crate) mod play;
+pub(crate) mod slots;
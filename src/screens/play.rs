@@ -1,83 +1,298 @@
-use std::{collections::HashMap, hash::Hash, time::Instant};
+use std::{fmt, time::Instant};
 
-use serde::Deserialize;
+use ratatui::layout::{Rect, Size};
+use serde::{
+    Deserialize, Deserializer,
+    de::{MapAccess, SeqAccess, Visitor},
+};
 
-use crate::engine::{
-    config, fs,
-    gfx::{self, ScreenRenderer},
+use crate::{
+    effects::madness,
+    engine::{
+        config,
+        dice::{Dice, Outcome},
+        fs,
+        gfx::{self, ScreenRenderer, TextPosition},
+        image_cache::ImageCache,
+        inventory::Item,
+        investigator::Investigator,
+        markup::{self, StyledText},
+        progress::Progress,
+        sanity::SanityCheck,
+        vars::{Condition, Effect},
+    },
 };
 
+// target of an action: either just the next screen index or a full choice, e.g.
+// `{"screen": 17, "if": {"not_set": "has_read_letter"}, "effects": [{"set": "has_read_letter"}]}`,
+// a choice can also wait for an item, e.g. `{"screen": 69, "if": {"has": "wilcox_address"}}`
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub(crate) enum NextScreen {
+    Screen(usize),
+    Choice {
+        screen: usize,
+        #[serde(rename = "if")]
+        condition: Option<Condition>,
+        #[serde(default)]
+        effects: Vec<Effect>,
+        check: Option<SkillCheck>,
+    },
+}
+
+// choice in the list form of `next`, e.g. `{"text": "Pokračuj...", "screen": 1}`
+#[derive(Deserialize)]
+struct ListedChoice {
+    text: String,
+    #[serde(flatten)]
+    next: NextScreen,
+}
+
+// choices of a screen in author order, written either as a list of choices or,
+// in older files, as a map from choice text to its target
+pub(crate) struct Next(Vec<(String, NextScreen)>);
+
+impl<'de> Deserialize<'de> for Next {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct NextVisitor;
+
+        impl<'de> Visitor<'de> for NextVisitor {
+            type Value = Next;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a list of choices or a map from choice text to screen")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Next, A::Error> {
+                let mut choices = Vec::new();
+                while let Some(choice) = seq.next_element::<ListedChoice>()? {
+                    choices.push((choice.text, choice.next));
+                }
+                Ok(Next(choices))
+            }
+
+            // entries are visited in file order, unlike a HashMap
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Next, A::Error> {
+                let mut choices = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    choices.push(entry);
+                }
+                Ok(Next(choices))
+            }
+        }
+
+        deserializer.deserialize_any(NextVisitor)
+    }
+}
+
+// percentile roll against a skill, e.g. `{"skill": "Library Use", "failure": 32, "hard": 18}`,
+// the choice's `screen` is reached on a regular success
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct SkillCheck {
+    pub(crate) skill: String,
+    pub(crate) failure: usize,
+    pub(crate) hard: Option<usize>,
+    pub(crate) extreme: Option<usize>,
+    pub(crate) fumble: Option<usize>,
+}
+
+impl SkillCheck {
+    // better outcomes fall back to the next lower level that has a screen
+    pub(crate) fn target(&self, outcome: Outcome, success: usize) -> usize {
+        match outcome {
+            Outcome::Extreme => self.extreme.or(self.hard).unwrap_or(success),
+            Outcome::Hard => self.hard.unwrap_or(success),
+            Outcome::Regular => success,
+            Outcome::Failure => self.failure,
+            Outcome::Fumble => self.fumble.unwrap_or(self.failure),
+        }
+    }
+}
+
 #[derive(Deserialize)]
 pub(crate) struct GameActions {
     // next screens with action text and next screen index
-    pub(crate) next: HashMap<String, usize>,
+    pub(crate) next: Next,
     pub(crate) location: Option<String>,
     pub(crate) ending: Option<bool>,
+    // applied to the story variables whenever the screen is entered
+    #[serde(default)]
+    pub(crate) on_enter: Vec<Effect>,
+    // sanity roll when the screen is entered
+    pub(crate) sanity: Option<SanityCheck>,
+}
+
+// a single choice offered to the player
+#[derive(Debug, Clone, PartialEq)]
+pub struct Choice {
+    pub(crate) text: String,
+    pub(crate) next: usize,
+    pub(crate) condition: Option<Condition>,
+    pub(crate) effects: Vec<Effect>,
+    pub(crate) check: Option<SkillCheck>,
+}
+
+impl Choice {
+    pub(crate) fn new(text: &str, next: usize) -> Self {
+        Self {
+            text: text.to_string(),
+            next,
+            condition: None,
+            effects: Vec::new(),
+            check: None,
+        }
+    }
+
+    // every screen the choice can lead to
+    pub(crate) fn targets(&self) -> Vec<usize> {
+        let mut targets = vec![self.next];
+        if let Some(check) = &self.check {
+            targets.push(check.failure);
+            targets.extend(check.hard);
+            targets.extend(check.extreme);
+            targets.extend(check.fumble);
+        }
+        targets
+    }
 }
 
 impl GameActions {
-    pub(crate) fn ending() -> Vec<(String, usize)> {
-        vec![("KONEC".to_string(), 0)]
+    pub(crate) fn ending() -> Vec<Choice> {
+        vec![Choice::new("KONEC", 0)]
+    }
+
+    // all choices of the screen, regardless of their conditions
+    pub(crate) fn choices(&self) -> Vec<Choice> {
+        self.next
+            .0
+            .iter()
+            .map(|(text, next)| match next {
+                NextScreen::Screen(screen) => Choice::new(text, *screen),
+                NextScreen::Choice {
+                    screen,
+                    condition,
+                    effects,
+                    check,
+                } => Choice {
+                    text: text.clone(),
+                    next: *screen,
+                    condition: condition.clone(),
+                    effects: effects.clone(),
+                    check: check.clone(),
+                },
+            })
+            .collect()
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum GameEvent {
     Exit,
-    NewScreen(usize),
+    NewScreen(Choice, usize), // the choice taken and its place in the menu
+    Back,                     // undo the last choice
     Ending,
 }
 #[derive(Debug, Clone, PartialEq)]
 pub enum GameState {
     Intro,
     Achievements,
+    Slots,
+    Investigator,
     Playing,
+    Journey,
     Ending,
 }
 
+// skill check in progress, animated in the text window before it resolves
+struct Roll {
+    choice: Choice,
+    selected: usize, // place of the choice in the menu
+    skill: u8,
+    roll: u8,
+    outcome: Outcome,
+    started: Instant,
+}
+
+// how long the dice keep tumbling before the result is shown, in seconds
+const ROLL_ANIMATION: f64 = 1.5;
+
 struct InnerConfig {
     pub(crate) scale_quality: bool,
-    pub(crate) notifications: bool,
+    pub(crate) data_path: String,
+    pub(crate) layout: Option<TextPosition>,
+    pub(crate) text_ratio: Option<f32>,
 }
 
+// key toggling the inventory and clue notebook
+const NOTEBOOK_KEY: char = 'i';
+
+// key toggling the full-screen view of the illustration
+const FULL_SCREEN_KEY: char = 'f';
+
+// seconds of typing skipped by each press (or key repeat) of the fast-forward key
+const FAST_FORWARD: f64 = 0.5;
+
 pub struct PlayScreen {
-    screen_no: usize,
     term_width: u16,
     term_height: u16,
+    split: gfx::Split,
+    full_screen: bool, // the illustration over the whole screen
     text_helper: gfx::TextHelper,
+    styled: StyledText,
+    scroll: gfx::TextScroll,
     timer: Instant,
     total_time_to_write: f32,
-    actions: Vec<(String, usize)>,
+    actions: Vec<Choice>,
     inner_config: InnerConfig,
     menu_selection: usize,
     image_names: Vec<String>,
     ending_screen: bool,
-    location: Option<String>,
+    investigator: Investigator,
+    dice: Dice,
+    roll: Option<Roll>,
+    notice: Option<String>,
+    madness: f32,
+    items: Vec<Item>,        // collected items followed by the clues
+    notebook: Option<usize>, // selected entry while the notebook is open
+    images: ImageCache,
 }
 
 impl PlayScreen {
     pub fn new(
         screen_no: usize,
-        term_width: u16,
-        term_height: u16,
+        size: Size,
         config: &config::Config,
+        progress: &Progress,
+        dice: Dice,
+        images: &ImageCache,
+        seen_before: bool,
     ) -> Result<Self, Box<dyn std::error::Error>> {
-        let text_helper = gfx::TextHelper::with_text(
-            config.text_speed,
-            fs::read_text(screen_no, config)
-                .unwrap_or(format!("Error reading screen {}", screen_no)),
-        );
-        let total_time_to_write = text_helper.time_to_finish();
+        let styled = match fs::read_text(screen_no, config) {
+            Ok(text) => markup::parse(&gfx::reflow(&text)),
+            Err(_) => StyledText::from(format!("Error reading screen {}", screen_no)),
+        };
+        // only the plain text is typed out, the styles are laid over it
+        let mut text_helper = gfx::TextHelper::with_text(config.text_speed, styled.text.clone());
+        // no need to wait for a text the player has already read
+        if seen_before || config.instant_text.unwrap_or(false) {
+            text_helper.reveal();
+        }
+        let total_time_to_write = text_helper.duration() as f32;
         let action_desc = fs::read_actions(screen_no, config)?;
         let ending_screen = action_desc.ending.unwrap_or(false);
         let location = action_desc.location.clone();
         let actions = if ending_screen {
             GameActions::ending()
+        } else if let Some(screen) = progress.redirect {
+            // insanity takes over, there is no choice left
+            vec![Choice::new("Pokračuj...", screen)]
         } else {
+            // hide the choices whose condition does not hold
             action_desc
-                .next
+                .choices()
                 .into_iter()
-                .collect::<Vec<(String, usize)>>()
+                .filter(|c| c.condition.as_ref().is_none_or(|c| progress.check(c)))
+                .collect()
         };
 
         let image_names = fs::get_image_names_for_screen(screen_no, config)?;
@@ -85,48 +300,165 @@ impl PlayScreen {
             return Err(format!("No images found for screen {}", screen_no).into());
         }
 
+        // items first, clues at the end of the notebook
+        let mut items = progress.inventory.resolve(&fs::read_items(config)?);
+        items.sort_by_key(|item| item.clue);
+
         let send_notifications = config.notifications.unwrap_or(false);
         if send_notifications {
-            Self::inform_location_change(location.clone());
+            Self::inform_location_change(location);
             if ending_screen {
-                Self::send_notification("Ending screen  🎉");
+                send_notification("Ending screen  🎉");
             }
         }
-        Ok(Self {
-            screen_no,
-            term_width,
-            term_height,
+        let screen = Self {
+            term_width: size.width,
+            term_height: size.height,
+            split: gfx::Split::new(config.layout, config.text_ratio, size.width, size.height),
+            full_screen: false,
             text_helper,
+            styled,
+            scroll: gfx::TextScroll::default(),
             total_time_to_write,
             timer: Instant::now(),
             actions,
             inner_config: InnerConfig {
                 scale_quality: config.scale_quality,
-                notifications: send_notifications,
+                data_path: config.data_path.clone(),
+                layout: config.layout,
+                text_ratio: config.text_ratio,
             },
             menu_selection: 0,
             image_names,
             ending_screen,
-            location,
-        })
+            investigator: progress.investigator.clone(),
+            dice,
+            roll: None,
+            notice: progress.notice.clone(),
+            madness: progress.madness(),
+            items,
+            notebook: None,
+            images: images.clone(),
+        };
+
+        // get the images ready while the player reads, the screen's own ones first
+        let mut paths = screen.image_names.clone();
+        if !ending_screen {
+            let mut targets = screen
+                .actions
+                .iter()
+                .flat_map(Choice::targets)
+                .collect::<Vec<_>>();
+            targets.sort_unstable();
+            targets.dedup();
+            for target in targets {
+                paths.extend(fs::get_image_names_for_screen(target, config).unwrap_or_default());
+            }
+        }
+        let (width, height) = screen.image_size();
+        images.preload(paths, width, height, config.scale_quality);
+        Ok(screen)
+    }
+
+    // size of the image, the whole screen or what the text window leaves over
+    fn image_size(&self) -> (u16, u16) {
+        let area = Rect::new(0, 0, self.term_width, self.term_height);
+        match self.split() {
+            Some(split) => {
+                let (_, image) = split.areas(area);
+                (image.width, image.height)
+            }
+            None => (area.width, area.height),
+        }
+    }
+
+    // highlight the choice at the given place in the menu, e.g. the one taken back
+    pub(crate) fn select_choice(&mut self, selected: usize) {
+        if selected < self.actions.len() {
+            self.menu_selection = selected;
+        }
+    }
+
+    fn start_roll(&mut self, choice: Choice, selected: usize) {
+        let Some(check) = &choice.check else {
+            return;
+        };
+        let skill = self.investigator.skill(&check.skill);
+        let roll = self.dice.d100();
+        self.roll = Some(Roll {
+            outcome: Outcome::of(roll, skill),
+            choice,
+            selected,
+            skill,
+            roll,
+            started: Instant::now(),
+        });
+    }
+
+    fn roll_text(&self) -> String {
+        let Some(roll) = &self.roll else {
+            return String::new();
+        };
+        let check = roll.choice.check.as_ref().map(|c| c.skill.as_str());
+        let header = format!("{} {}%", check.unwrap_or_default(), roll.skill);
+        if roll.started.elapsed().as_secs_f64() < ROLL_ANIMATION {
+            // tumbling dice, a new number every frame; drawn from the unseeded thread
+            // generator so that the frame rate does not change the seeded rolls
+            format!("{}\nRolling... {:02}", header, rand::random_range(1..=100))
+        } else {
+            format!(
+                "{}\nRolled {:02}: {}\n\n---> Continue",
+                header,
+                roll.roll,
+                roll.outcome.describe()
+            )
+        }
+    }
+
+    fn notebook_text(&self, selected: usize) -> String {
+        if self.items.is_empty() {
+            return "Inventory is empty.\n\n---> Back".to_string();
+        }
+        let mut contents = String::new();
+        let mut section = None;
+        for (i, item) in self.items.iter().enumerate() {
+            if section != Some(item.clue) {
+                section = Some(item.clue);
+                let title = if item.clue { "Notebook" } else { "Inventory" };
+                contents.push_str(&format!("\n{}\n", title));
+            }
+            let marker = if i == selected { "--->" } else { "    " };
+            contents.push_str(&format!("{} {}\n", marker, item.name));
+        }
+        if let Some(item) = self.items.get(selected) {
+            contents.push_str(&format!("\n{}\n", item.description));
+        }
+        contents
+    }
+
+    // the selected item's picture, if it has one
+    fn notebook_image(&self) -> Option<String> {
+        let item = self.items.get(self.notebook?)?;
+        let image = item.image.as_ref()?;
+        Some(format!("{}/images/{}", self.inner_config.data_path, image))
     }
 
     fn inform_location_change(location: Option<String>) {
         if let Some(location) = location {
-            Self::send_notification(&format!("Location: {}", location));
+            send_notification(&format!("Location: {}", location));
         }
     }
+}
 
-    fn send_notification(text: &str) {
-        notify_rust::Notification::new()
-            .summary("Call of Cthulhu")
-            .body(text)
-            .show()
-            .ok();
-    }
+pub(crate) fn send_notification(text: &str) {
+    notify_rust::Notification::new()
+        .summary("Call of Cthulhu")
+        .body(text)
+        .show()
+        .ok();
 }
 
-fn actions_text(actions: &Vec<(String, usize)>, idx: usize) -> String {
+fn actions_text(actions: &[Choice], idx: usize) -> String {
     let mut contents = String::new();
     let l = actions.len();
     if l == 0 {
@@ -134,14 +466,12 @@ fn actions_text(actions: &Vec<(String, usize)>, idx: usize) -> String {
         return contents;
     }
     let idx = idx % l;
-    let mut i = 0;
-    for (action_text, next_screen) in actions.iter() {
+    for (i, action) in actions.iter().enumerate() {
         if i == idx {
-            contents.push_str(&format!("---> {}\n", action_text));
+            contents.push_str(&format!("---> {}\n", action.text));
         } else {
-            contents.push_str(&format!("     {}\n", action_text));
+            contents.push_str(&format!("     {}\n", action.text));
         }
-        i += 1;
     }
     contents
 }
@@ -149,43 +479,164 @@ fn actions_text(actions: &Vec<(String, usize)>, idx: usize) -> String {
 impl ScreenRenderer<GameEvent> for PlayScreen {
     fn render(&mut self) -> Vec<Vec<(u8, u8, u8)>> {
         // if multiple images are present for the given screen...
+        let area = Rect::new(0, 0, self.term_width, self.term_height);
+        let (tw, th) = match self.split() {
+            Some(split) => {
+                let (_, image) = split.areas(area);
+                (image.width, image.height)
+            }
+            None => (area.width, area.height),
+        };
+        if let Some(image) = self.notebook_image()
+            && let Ok(image) = self
+                .images
+                .get(&image, tw, th, self.inner_config.scale_quality)
+        {
+            return image.to_vec();
+        }
         let l = self.image_names.len();
         // ... compute the time that should be spent on each image ...
         let per_image_time = self.total_time_to_write / l as f32;
         // ... and cycle the images on screen
         let idx = (self.timer.elapsed().as_secs_f64() / per_image_time as f64) as usize % l;
-        let tw = self.term_width - self.text_window_sz();
-        if let Ok((_, _, screen)) = fs::read_image(
+        if let Ok(image) = self.images.get(
             &self.image_names[idx],
             tw,
-            self.term_height,
+            th,
             self.inner_config.scale_quality,
-            true,
         ) {
-            return screen;
+            let mut screen = image.to_vec();
+            madness::apply(
+                &mut screen,
+                self.madness,
+                self.timer.elapsed().as_secs_f32(),
+            );
+            screen
         } else {
             // blue screen of death
-            return vec![vec![(0, 0, 255); tw as usize]; self.term_height as usize];
+            let (cw, ch) = self.images.cell_size();
+            vec![vec![(0, 0, 255); (tw * cw) as usize]; (th * ch) as usize]
         }
     }
 
-    fn text(&mut self) -> String {
+    fn text(&mut self) -> StyledText {
+        if let Some(selected) = self.notebook {
+            return self.notebook_text(selected).into();
+        }
         let text = self.text_helper.get_text().unwrap_or_default();
-        if let Some(when) = self.text_helper.text_reached_end() {
-            let actions_text = actions_text(&self.actions, self.menu_selection);
-            format!("{}\n\n{}", text, actions_text)
+        match &self.notice {
+            Some(notice) if self.text_helper.text_reached_end().is_some() => {
+                self.styled.with_text(format!("{}\n\n[{}]", text, notice))
+            }
+            _ => self.styled.with_text(text),
+        }
+    }
+
+    fn choices(&mut self) -> Option<String> {
+        if self.notebook.is_some() {
+            None
+        } else if self.roll.is_some() {
+            Some(format!("\n{}", self.roll_text()))
+        } else if self.text_helper.text_reached_end().is_some() {
+            Some(format!(
+                "\n{}",
+                actions_text(&self.actions, self.menu_selection).trim_end()
+            ))
         } else {
-            text
+            None
         }
     }
 
-    fn text_window_sz(&self) -> u16 {
-        self.term_width / 4
+    fn text_scroll(&mut self) -> Option<&mut gfx::TextScroll> {
+        Some(&mut self.scroll)
+    }
+
+    fn split(&self) -> Option<gfx::Split> {
+        (!self.full_screen).then_some(self.split)
+    }
+
+    fn resize(&mut self, width: u16, height: u16) {
+        self.term_width = width;
+        self.term_height = height;
+        self.split = gfx::Split::new(
+            self.inner_config.layout,
+            self.inner_config.text_ratio,
+            width,
+            height,
+        );
+    }
+
+    fn side_panel(&self) -> Option<Vec<String>> {
+        Some(self.investigator.summary())
     }
 
     fn key_event(&mut self, key_code: crossterm::event::KeyCode) -> Option<GameEvent> {
+        if self.full_screen {
+            // any key brings the text back
+            self.full_screen = false;
+            return None;
+        }
+        if key_code == crossterm::event::KeyCode::Char(FULL_SCREEN_KEY) {
+            self.full_screen = true;
+            return None;
+        }
+        if let Some(roll) = &self.roll {
+            // the dice have to settle before the outcome can be followed
+            if key_code != crossterm::event::KeyCode::Enter
+                || roll.started.elapsed().as_secs_f64() < ROLL_ANIMATION
+            {
+                return None;
+            }
+            let mut choice = roll.choice.clone();
+            if let Some(check) = choice.check.take() {
+                choice.next = check.target(roll.outcome, choice.next);
+            }
+            let selected = roll.selected;
+            self.roll = None;
+            return Some(GameEvent::NewScreen(choice, selected));
+        }
+        if let Some(selected) = self.notebook {
+            match key_code {
+                crossterm::event::KeyCode::Esc
+                | crossterm::event::KeyCode::Enter
+                | crossterm::event::KeyCode::Char(NOTEBOOK_KEY) => self.notebook = None,
+                crossterm::event::KeyCode::Down if selected + 1 < self.items.len() => {
+                    self.notebook = Some(selected + 1)
+                }
+                crossterm::event::KeyCode::Up if selected > 0 => self.notebook = Some(selected - 1),
+                _ => {}
+            }
+            return None;
+        }
+        if self.text_helper.is_typing() {
+            match key_code {
+                crossterm::event::KeyCode::Enter | crossterm::event::KeyCode::Char(' ') => {
+                    self.text_helper.reveal();
+                    return None;
+                }
+                // holding the key keeps skipping ahead with the key repeat
+                crossterm::event::KeyCode::Right => {
+                    self.text_helper.accelerate(FAST_FORWARD);
+                    return None;
+                }
+                _ => {}
+            }
+        }
         match key_code {
+            crossterm::event::KeyCode::PageUp => {
+                self.scroll.page_up();
+                None
+            }
+            crossterm::event::KeyCode::PageDown => {
+                self.scroll.page_down();
+                None
+            }
+            crossterm::event::KeyCode::Char(NOTEBOOK_KEY) => {
+                self.notebook = Some(0);
+                None
+            }
             crossterm::event::KeyCode::Esc => Some(GameEvent::Exit),
+            crossterm::event::KeyCode::Backspace => Some(GameEvent::Back),
             crossterm::event::KeyCode::Down => {
                 if self.menu_selection + 1 < self.actions.len() {
                     self.menu_selection += 1;
@@ -199,11 +650,14 @@ impl ScreenRenderer<GameEvent> for PlayScreen {
                 None
             }
             crossterm::event::KeyCode::Enter => {
-                if let Some((_, next_screen)) = self.actions.get(self.menu_selection) {
+                if let Some(choice) = self.actions.get(self.menu_selection) {
                     if self.ending_screen {
-                        return Some(GameEvent::Ending);
+                        Some(GameEvent::Ending)
+                    } else if choice.check.is_some() {
+                        self.start_roll(choice.clone(), self.menu_selection);
+                        None
                     } else {
-                        return Some(GameEvent::NewScreen(*next_screen));
+                        Some(GameEvent::NewScreen(choice.clone(), self.menu_selection))
                     }
                 } else {
                     None
@@ -213,3 +667,155 @@ impl ScreenRenderer<GameEvent> for PlayScreen {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn choices(json: &str) -> Vec<Choice> {
+        serde_json::from_str::<GameActions>(json).unwrap().choices()
+    }
+
+    #[test]
+    fn map_form_keeps_the_file_order() {
+        let choices = choices(r#"{"next": {"z": 3, "a": 1, "m": 2}}"#);
+        let order = choices
+            .iter()
+            .map(|c| (c.text.as_str(), c.next))
+            .collect::<Vec<_>>();
+        assert_eq!(order, vec![("z", 3), ("a", 1), ("m", 2)]);
+    }
+
+    #[test]
+    fn list_form_reads_plain_and_full_choices() {
+        let choices = choices(
+            r#"{"next": [
+                {"text": "dál", "screen": 1},
+                {"text": "číst", "screen": 17, "if": {"not_set": "read"},
+                 "effects": [{"set": "read"}]},
+                {"text": "hledat", "screen": 5,
+                 "check": {"skill": "Spot Hidden", "failure": 6, "hard": 7}}
+            ]}"#,
+        );
+        assert_eq!(choices[0], Choice::new("dál", 1));
+        assert_eq!(
+            choices[1].condition,
+            Some(Condition::NotSet("read".to_string()))
+        );
+        assert_eq!(choices[1].effects, vec![Effect::Set("read".to_string())]);
+        let check = choices[2].check.as_ref().unwrap();
+        assert_eq!(check.skill, "Spot Hidden");
+        assert_eq!(choices[2].targets(), vec![5, 6, 7]);
+    }
+
+    #[test]
+    fn choices_with_the_same_text_stay_apart() {
+        use crossterm::event::KeyCode;
+
+        let twins = choices(
+            r#"{"next": [
+                {"text": "dál", "screen": 1},
+                {"text": "dál", "screen": 17, "effects": [{"set": "read"}]}
+            ]}"#,
+        );
+        let order = twins
+            .iter()
+            .map(|c| (c.text.as_str(), c.next))
+            .collect::<Vec<_>>();
+        assert_eq!(order, vec![("dál", 1), ("dál", 17)]);
+
+        // the menu tells them apart by their place, not their text
+        let mut play = screen(1);
+        play.actions = twins;
+        play.select_choice(1);
+        let Some(GameEvent::NewScreen(choice, selected)) = play.key_event(KeyCode::Enter) else {
+            panic!("no choice taken");
+        };
+        assert_eq!((choice.next, selected), (17, 1));
+        play.select_choice(0);
+        let Some(GameEvent::NewScreen(choice, selected)) = play.key_event(KeyCode::Enter) else {
+            panic!("no choice taken");
+        };
+        assert_eq!((choice.next, selected), (1, 0));
+    }
+
+    #[test]
+    fn map_values_are_screens_or_choices() {
+        let choices = choices(
+            r#"{"next": {"dál": 1, "do Londýna": {"screen": 95, "if": {"has": "clippings"}}}}"#,
+        );
+        assert_eq!(choices[0], Choice::new("dál", 1));
+        assert_eq!(choices[1].next, 95);
+        assert_eq!(
+            choices[1].condition,
+            Some(Condition::Has("clippings".to_string()))
+        );
+    }
+
+    #[test]
+    fn malformed_next_is_an_error() {
+        assert!(serde_json::from_str::<GameActions>(r#"{"next": 5}"#).is_err());
+        assert!(serde_json::from_str::<GameActions>(r#"{"next": [{"screen": 5}]}"#).is_err());
+        assert!(serde_json::from_str::<GameActions>(r#"{"next": {"dál": "x"}}"#).is_err());
+    }
+
+    fn screen(screen_no: usize) -> PlayScreen {
+        let cfg = config::Config {
+            data_path: "assets/".to_string(),
+            ..config::Config::default()
+        };
+        let images = ImageCache::new((8, 16));
+        PlayScreen::new(
+            screen_no,
+            Size::new(80, 24),
+            &cfg,
+            &Progress::default(),
+            Dice::new(Some(1)),
+            &images,
+            true,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn taken_choices_report_their_place_in_the_menu() {
+        use crossterm::event::KeyCode;
+
+        let mut play = screen(1);
+        play.key_event(KeyCode::Down);
+        let Some(GameEvent::NewScreen(choice, selected)) = play.key_event(KeyCode::Enter) else {
+            panic!("no choice taken");
+        };
+        assert_eq!((choice.next, selected), (17, 1));
+
+        // the outcome of a roll leads elsewhere, the place in the menu stays
+        play.roll = Some(Roll {
+            choice: Choice {
+                check: Some(SkillCheck {
+                    skill: "Spot Hidden".to_string(),
+                    failure: 9,
+                    hard: None,
+                    extreme: None,
+                    fumble: None,
+                }),
+                ..play.actions[2].clone()
+            },
+            selected: 2,
+            skill: 25,
+            roll: 90,
+            outcome: Outcome::Failure,
+            started: Instant::now() - std::time::Duration::from_secs(2),
+        });
+        let Some(GameEvent::NewScreen(choice, selected)) = play.key_event(KeyCode::Enter) else {
+            panic!("no choice taken");
+        };
+        assert_eq!((choice.next, selected), (9, 2));
+
+        // taking the choice back highlights it again
+        let mut play = screen(1);
+        play.select_choice(selected);
+        assert_eq!(play.menu_selection, 2);
+        play.select_choice(7);
+        assert_eq!(play.menu_selection, 2, "no such choice");
+    }
+}
@@ -0,0 +1,196 @@
+use std::io;
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    Terminal,
+    prelude::CrosstermBackend,
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, List, ListState, Paragraph},
+};
+
+use crate::engine::{config, fs, gfx::Painter, save};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SlotMode {
+    Save,
+    Load,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SlotEvent {
+    Exit,
+    Save(String), // save into the slot with the given name
+    Load(String), // load the slot with the given name
+}
+
+pub struct SlotScreen {
+    mode: SlotMode,
+    slots: Vec<save::SaveGame>,
+    image: Vec<Vec<(u8, u8, u8)>>,
+    painter: Painter,
+    list_state: ListState,
+    new_slot_name: Option<String>, // name being typed for a new slot
+    status: Option<String>,
+}
+
+const NEW_SLOT: &str = "[ New slot ]";
+
+impl SlotScreen {
+    pub fn new(
+        width: usize,
+        height: usize,
+        config: &config::Config,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let image = fs::load_intro_screen_image(width as u16, height as u16, config)?;
+        Ok(Self {
+            mode: SlotMode::Load,
+            slots: Vec::new(),
+            image,
+            painter: Painter::new(config),
+            list_state: ListState::default(),
+            new_slot_name: None,
+            status: None,
+        })
+    }
+
+    // re-read the save directory and switch the picker into the given mode
+    pub fn open(&mut self, mode: SlotMode, config: &config::Config) {
+        self.mode = mode;
+        self.slots = save::list_slots(config);
+        self.new_slot_name = None;
+        self.status = None;
+        self.list_state
+            .select(if self.items_len() > 0 { Some(0) } else { None });
+    }
+
+    // shown below the slots, e.g. why a save failed
+    pub fn set_status(&mut self, status: String) {
+        self.status = Some(status);
+    }
+
+    fn items_len(&self) -> usize {
+        match self.mode {
+            SlotMode::Save => self.slots.len() + 1,
+            SlotMode::Load => self.slots.len(),
+        }
+    }
+
+    pub fn render(
+        &self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        terminal.draw(|f| {
+            let area = f.area();
+
+            self.painter.draw(f.buffer_mut(), area, &self.image);
+
+            let title = match self.mode {
+                SlotMode::Save => "Save Game",
+                SlotMode::Load => "Load Game",
+            };
+            let mut block = Block::default().borders(Borders::ALL).title(title);
+            if let Some(status) = &self.status {
+                block = block.title_bottom(Line::styled(
+                    status.clone(),
+                    Style::default().fg(Color::Yellow),
+                ));
+            }
+
+            if let Some(name) = &self.new_slot_name {
+                let prompt = Paragraph::new(format!("Slot name: {}_", name))
+                    .style(Style::default().fg(Color::Yellow))
+                    .block(block);
+                f.render_widget(prompt, area);
+            } else if self.items_len() == 0 {
+                let empty = Paragraph::new("No saved games.")
+                    .style(Style::default().fg(Color::White))
+                    .block(block);
+                f.render_widget(empty, area);
+            } else {
+                f.render_stateful_widget(self.list(block), area, &mut self.list_state.clone());
+            }
+            self.painter.restore(f.buffer_mut(), area, &self.image);
+        })?;
+
+        Ok(())
+    }
+
+    fn list<'a>(&self, block: Block<'a>) -> List<'a> {
+        let mut items = self
+            .slots
+            .iter()
+            .map(|s| Line::from(s.describe()))
+            .collect::<Vec<_>>();
+        if self.mode == SlotMode::Save {
+            items.push(Line::from(NEW_SLOT));
+        }
+        List::new(items)
+            .block(block)
+            .style(Style::default().fg(Color::White))
+            .highlight_style(
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("> ")
+    }
+
+    pub fn key_event(&mut self, key_code: KeyCode) -> Option<SlotEvent> {
+        if let Some(name) = &mut self.new_slot_name {
+            match key_code {
+                KeyCode::Char(c) => name.push(c),
+                KeyCode::Backspace => {
+                    name.pop();
+                }
+                KeyCode::Esc => self.new_slot_name = None,
+                KeyCode::Enter => {
+                    let name = name.trim().to_string();
+                    if !name.is_empty() {
+                        self.new_slot_name = None;
+                        return Some(SlotEvent::Save(name));
+                    }
+                }
+                _ => {}
+            }
+            return None;
+        }
+
+        match key_code {
+            KeyCode::Esc => Some(SlotEvent::Exit),
+            KeyCode::Up => {
+                if let Some(selected) = self.list_state.selected()
+                    && selected > 0
+                {
+                    self.list_state.select(Some(selected - 1));
+                }
+                None
+            }
+            KeyCode::Down => {
+                if let Some(selected) = self.list_state.selected()
+                    && selected + 1 < self.items_len()
+                {
+                    self.list_state.select(Some(selected + 1));
+                }
+                None
+            }
+            KeyCode::Enter => {
+                let selected = self.list_state.selected()?;
+                match (self.slots.get(selected), &self.mode) {
+                    (Some(slot), SlotMode::Save) => Some(SlotEvent::Save(slot.name.clone())),
+                    (Some(slot), SlotMode::Load) => Some(SlotEvent::Load(slot.name.clone())),
+                    (None, SlotMode::Save) => {
+                        self.new_slot_name = Some(format!("Slot {}", self.slots.len() + 1));
+                        None
+                    }
+                    (None, SlotMode::Load) => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    pub fn get_slot(&self, name: &str) -> Option<save::SaveGame> {
+        self.slots.iter().find(|s| s.name == name).cloned()
+    }
+}